@@ -13,29 +13,83 @@ fn load_field(index: u32) -> Option<DefinedField> {
 fn solver_benchmarks(c: &mut Criterion) {
     let mut group = c.benchmark_group("solver");
     group.measurement_time(std::time::Duration::from_secs(10));
+    let config = SolverConfig::default();
+    print_sat_savings();
 
     for i in 1..=100 {
         if let Some(field) = load_field(i) {
             let field_id = format!("field_{:03}", i);
-            group.bench_function(&field_id, |b| b.iter(|| is_solvable(black_box(&field))));
+            group.bench_function(&field_id, |b| {
+                b.iter(|| is_solvable(black_box(&field), &config))
+            });
         }
     }
 
     group.finish();
 }
 
+/// Prints where a single solving run spends its time, next to the timings of criterion
+fn print_stats(name: &str, field: &impl MineSweeperField, config: &SolverConfig) {
+    let mut solver = Solver::new(field, config);
+    let _ = solver.solve();
+    println!("{}:\n{}\n", name, solver.get_stats());
+}
+
+/// Prints how many SAT runs the polynomial strategies in front of it save on the benchmark fields
+fn print_sat_savings() {
+    let without = |excluded: &[SolvingStrategy]| {
+        SolverConfig::new(
+            SolvingStrategy::iter()
+                .filter(|s| !excluded.contains(s))
+                .collect(),
+        )
+    };
+    let configs = [
+        (
+            "neither",
+            without(&[SolvingStrategy::BoxLogic, SolvingStrategy::LinearAlgebra]),
+        ),
+        ("BoxLogic", without(&[SolvingStrategy::LinearAlgebra])),
+        ("LinearAlgebra", without(&[SolvingStrategy::BoxLogic])),
+        ("both", SolverConfig::default()),
+    ];
+    let sat_runs = |field: &DefinedField, config: &SolverConfig| {
+        let mut solver = Solver::new(field, config);
+        let _ = solver.solve();
+        let stats = solver.get_stats();
+        stats
+            .strategies
+            .iter()
+            .find(|s| s.name == "Sat")
+            .map_or(0, |s| s.invocations)
+    };
+
+    let fields: Vec<DefinedField> = (1..=100).filter_map(load_field).collect();
+    println!("SAT runs on the benchmark fields:");
+    for (name, config) in &configs {
+        let runs: usize = fields.iter().map(|field| sat_runs(field, config)).sum();
+        println!("  {:<14} {}", name, runs);
+    }
+    println!();
+}
+
 fn evil_field_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("solver_evil");
     group.measurement_time(std::time::Duration::from_secs(10));
+    let config = SolverConfig::default();
 
     if let Ok(field) = DefinedField::from_file("generated/testing/evil_ng_field.minesweeper") {
+        print_stats("evil_ng_field", &field, &config);
         group.bench_function("evil_ng_field", |b| {
-            b.iter(|| is_solvable(black_box(&field)))
+            b.iter(|| is_solvable(black_box(&field), &config))
         });
     }
 
     if let Ok(field) = DefinedField::from_file("generated/testing/hard.minesweeper") {
-        group.bench_function("hard_field", |b| b.iter(|| is_solvable(black_box(&field))));
+        print_stats("hard_field", &field, &config);
+        group.bench_function("hard_field", |b| {
+            b.iter(|| is_solvable(black_box(&field), &config))
+        });
     }
 
     group.finish();
@@ -60,10 +114,45 @@ fn ng_generation_benchmarks(c: &mut Criterion) {
     group.finish();
 }
 
+fn strategy_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solver_strategies");
+    group.measurement_time(std::time::Duration::from_secs(10));
+
+    let configs = [
+        ("simple", vec![SolvingStrategy::Simple]),
+        (
+            "simple_reduction",
+            vec![SolvingStrategy::Simple, SolvingStrategy::Reduction],
+        ),
+        ("box_logic", vec![SolvingStrategy::BoxLogic]),
+        ("linear_algebra", vec![SolvingStrategy::LinearAlgebra]),
+        ("sat", vec![SolvingStrategy::Sat]),
+    ];
+
+    for field_name in ["hard", "evil_ng_field"] {
+        let Ok(field) =
+            DefinedField::from_file(&format!("generated/testing/{}.minesweeper", field_name))
+        else {
+            continue;
+        };
+
+        for (name, strategies) in &configs {
+            let config = SolverConfig::new(strategies.clone());
+            print_stats(&format!("{}/{}", field_name, name), &field, &config);
+            group.bench_function(format!("{}/{}", field_name, name), |b| {
+                b.iter(|| is_solvable(black_box(&field), &config))
+            });
+        }
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     solver_benchmarks,
     evil_field_benchmark,
+    strategy_benchmarks,
     ng_generation_benchmarks
 );
 criterion_main!(benches);
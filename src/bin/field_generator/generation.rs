@@ -1,21 +1,24 @@
 use super::params::Params;
+use super::rating;
 use super::screen::ProgressEvent;
 use minesweeper_ng_gen::*;
 use std::{
     fs,
     sync::{
-        Arc,
+        Arc, Mutex,
         atomic::{AtomicU32, Ordering},
         mpsc,
     },
 };
 
+/// Generates the fields in the background. Cancelling the returned token stops the no-guess solvers.
 pub fn start_generation(
     params: &Params,
 ) -> (
     Arc<AtomicU32>,
     Arc<AtomicU32>,
     mpsc::Receiver<ProgressEvent>,
+    CancellationToken,
 ) {
     let total = params.count_val().unwrap();
     let w = params.width_val().unwrap();
@@ -30,11 +33,15 @@ pub fn start_generation(
     let errors2 = errors.clone();
 
     let (tx, rx) = mpsc::channel::<ProgressEvent>();
+    let cancel = CancellationToken::new();
+    let config =
+        SolverConfig::default().with_budget(SolverBudget::new().with_cancellation(cancel.clone()));
 
     rayon::spawn(move || {
         fs::create_dir_all(&output).ok();
 
         let is_single = total == 1;
+        let ratings = Mutex::new(Vec::new());
         rayon::scope(|s| {
             for id in 1..=total {
                 let tx = tx.clone();
@@ -42,6 +49,8 @@ pub fn start_generation(
                 let output = output.clone();
                 let done2 = done2.clone();
                 let errors2 = errors2.clone();
+                let ratings = &ratings;
+                let config = &config;
                 s.spawn(move |_| {
                     let filename = if is_single {
                         format!("{}.minesweeper", output)
@@ -49,12 +58,20 @@ pub fn start_generation(
                         format!("{}/{}.minesweeper", output, id)
                     };
                     let success = if no_guess {
-                        NoGuessField::new(w, h, mines)
-                            .and_then(|f| f.to_file(&filename).map_err(|e| e.into()))
+                        NoGuessField::with_config(w, h, mines, config)
+                            .and_then(|f| {
+                                let rating = rating::rate(&f, config, true, false);
+                                ratings.lock().unwrap().push((id, rating));
+                                f.to_file(&filename).map_err(|e| e.into())
+                            })
                             .is_ok()
                     } else {
                         RandomField::new(w, h, mines)
-                            .and_then(|f| f.to_file(&filename).map_err(|e| e.into()))
+                            .and_then(|f| {
+                                let rating = rating::rate(&f, config, false, false);
+                                ratings.lock().unwrap().push((id, rating));
+                                f.to_file(&filename).map_err(|e| e.into())
+                            })
                             .is_ok()
                     };
                     if !success {
@@ -65,8 +82,11 @@ pub fn start_generation(
                 });
             }
         });
+        if !is_single {
+            let _ = rating::write_ratings(&output, &mut ratings.into_inner().unwrap());
+        }
         let _ = tx.send(ProgressEvent::AllDone);
     });
 
-    (done, errors, rx)
+    (done, errors, rx, cancel)
 }
@@ -1,7 +1,9 @@
 use super::command::CommandResult;
+use crate::rating;
 use indicatif::{ProgressBar, ProgressStyle};
 use minesweeper_ng_gen::*;
 use rayon::prelude::*;
+use std::sync::Mutex;
 use std::time::Instant;
 use std::{fs, io, path::Path};
 
@@ -26,15 +28,35 @@ pub fn generate_fields(field_data: CommandResult) {
     );
     progress.set_message("Generating fields...");
 
+    let ratings = Mutex::new(Vec::new());
     let start_time = Instant::now();
     (1..=field_data.count).into_par_iter().for_each(|id| {
         let filename = format!("{}/{}.minesweeper", field_data.output, id);
         let result = if field_data.no_guess {
-            NoGuessField::new(field_data.width, field_data.height, field_data.mine_spec)
-                .map(|field| field.to_file(&filename))
+            NoGuessField::with_config(
+                field_data.width,
+                field_data.height,
+                field_data.mine_spec,
+                &field_data.solver_config(),
+            )
+            .map(|field| {
+                let rating = rating::rate(&field, &field_data.solver_config(), true, false);
+                ratings.lock().unwrap().push((id, rating));
+                field.to_file(&filename)
+            })
         } else {
-            RandomField::new(field_data.width, field_data.height, field_data.mine_spec)
-                .map(|field| field.to_file(&filename))
+            RandomField::new(field_data.width, field_data.height, field_data.mine_spec).map(
+                |field| {
+                    let rating = rating::rate(
+                        &field,
+                        &field_data.solver_config(),
+                        false,
+                        field_data.ambiguity,
+                    );
+                    ratings.lock().unwrap().push((id, rating));
+                    field.to_file(&filename)
+                },
+            )
         };
         if let Err(err) = result {
             progress.println(format!("Error generating field {}: {}", id, err));
@@ -47,6 +69,16 @@ pub fn generate_fields(field_data: CommandResult) {
         start_time.elapsed()
     ));
     println!("Saved Files to '{}'", field_data.output);
+
+    let mut ratings = ratings.into_inner().unwrap();
+    match rating::write_ratings(&field_data.output, &mut ratings) {
+        Ok(()) => println!(
+            "Saved ratings to '{}/{}'",
+            field_data.output,
+            rating::RATINGS_FILE
+        ),
+        Err(err) => eprintln!("Error writing ratings: {}", err),
+    }
 }
 
 fn setup_output_directory(output: &String) -> io::Result<()> {
@@ -1,6 +1,7 @@
 use clap::{Arg, ArgAction, ArgGroup, Command};
-use minesweeper_ng_gen::Mines;
+use minesweeper_ng_gen::{Mines, SolverBudget, SolverConfig};
 use std::process::exit;
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct CommandResult {
@@ -10,6 +11,25 @@ pub struct CommandResult {
     pub count: u32,
     pub no_guess: bool,
     pub output: String,
+    /// Time limit per field
+    pub timeout: Option<Duration>,
+    pub max_search_nodes: Option<usize>,
+    /// Report where fields which aren't no-guess need guessing
+    pub ambiguity: bool,
+}
+
+impl CommandResult {
+    /// Solver config for generating one field, the timeout starts now
+    pub fn solver_config(&self) -> SolverConfig {
+        let mut budget = SolverBudget::new();
+        if let Some(timeout) = self.timeout {
+            budget = budget.with_timeout(timeout);
+        }
+        if let Some(nodes) = self.max_search_nodes {
+            budget = budget.with_max_search_nodes(nodes);
+        }
+        SolverConfig::default().with_budget(budget)
+    }
 }
 
 pub fn execute_command() -> CommandResult {
@@ -18,6 +38,7 @@ pub fn execute_command() -> CommandResult {
     match app.get_matches().subcommand() {
         Some(("generate", sub_matches)) => {
             let (width, height, mine_spec, no_guess, output) = parse_common_args(sub_matches);
+            let (timeout, max_search_nodes) = parse_budget_args(sub_matches);
 
             CommandResult {
                 width,
@@ -26,11 +47,15 @@ pub fn execute_command() -> CommandResult {
                 count: 1,
                 no_guess,
                 output: output + ".minesweeper",
+                timeout,
+                max_search_nodes,
+                ambiguity: sub_matches.get_flag("ambiguity"),
             }
         }
         Some(("batch", sub_matches)) => {
             let (width, height, mine_spec, no_guess, output) = parse_common_args(sub_matches);
             let count = *sub_matches.get_one::<u32>("count").unwrap();
+            let (timeout, max_search_nodes) = parse_budget_args(sub_matches);
 
             CommandResult {
                 width,
@@ -39,6 +64,9 @@ pub fn execute_command() -> CommandResult {
                 count,
                 no_guess,
                 output,
+                timeout,
+                max_search_nodes,
+                ambiguity: sub_matches.get_flag("ambiguity"),
             }
         }
         _ => {
@@ -91,6 +119,24 @@ fn build_command() -> Command {
                     .help("Generate no-guess fields (solvable without guessing)")
                     .action(ArgAction::SetTrue),
             )
+            .arg(
+                Arg::new("timeout")
+                    .long("timeout")
+                    .help("Give up generating a no-guess field after this many seconds")
+                    .value_parser(clap::value_parser!(f64)),
+            )
+            .arg(
+                Arg::new("max-search-nodes")
+                    .long("max-search-nodes")
+                    .help("Search nodes the solver may explore per component before giving up")
+                    .value_parser(clap::value_parser!(usize)),
+            )
+            .arg(
+                Arg::new("ambiguity")
+                    .long("ambiguity")
+                    .help("Report how many guesses fields which aren't no-guess need")
+                    .action(ArgAction::SetTrue),
+            )
             .arg(
                 Arg::new("output")
                     .short('o')
@@ -167,3 +213,15 @@ fn parse_common_args(sub_matches: &clap::ArgMatches) -> (u32, u32, Mines, bool,
 
     (width, height, mine_spec, no_guess, output_directory)
 }
+
+fn parse_budget_args(sub_matches: &clap::ArgMatches) -> (Option<Duration>, Option<usize>) {
+    let timeout = sub_matches.get_one::<f64>("timeout").map(|&seconds| {
+        Duration::try_from_secs_f64(seconds).unwrap_or_else(|_| {
+            eprintln!("Error: Invalid timeout");
+            exit(2);
+        })
+    });
+    let max_search_nodes = sub_matches.get_one::<usize>("max-search-nodes").copied();
+
+    (timeout, max_search_nodes)
+}
@@ -1,4 +1,5 @@
 use super::command::CommandResult;
+use crate::rating;
 use minesweeper_ng_gen::*;
 use std::{path::Path, process::exit};
 
@@ -18,15 +19,46 @@ pub fn generate_field(field_data: CommandResult) {
             });
             println!("Field generated and saved to: {}", $filename);
             $field.show();
+
+            let rating = rating::rate(
+                &$field,
+                &field_data.solver_config(),
+                field_data.no_guess,
+                field_data.ambiguity,
+            );
+            println!("Metrics: {}", rating::describe_metrics(&rating.metrics));
+            if let Some(difficulty) = &rating.difficulty {
+                println!("Difficulty: {}", rating::describe_difficulty(difficulty));
+            }
+            if let Some(ambiguity) = &rating.ambiguity {
+                println!("Needs guessing: {}", ambiguity);
+            }
+            if let Some(clicks) = &rating.clicks {
+                println!("Perfect play: {}", rating::describe_clicks(clicks));
+                #[cfg(feature = "json")]
+                {
+                    let replay =
+                        $filename.trim_end_matches(".minesweeper").to_string() + ".clicks.json";
+                    match std::fs::write(&replay, clicks.as_json()) {
+                        Ok(()) => println!("Click sequence saved to: {}", replay),
+                        Err(err) => eprintln!("Error writing to file {}: {}", replay, err),
+                    }
+                }
+            }
         }};
     }
 
     if field_data.no_guess {
-        let field = NoGuessField::new(field_data.width, field_data.height, field_data.mine_spec)
-            .unwrap_or_else(|err| {
-                eprintln!("Error: {}", err);
-                exit(1);
-            });
+        let field = NoGuessField::with_config(
+            field_data.width,
+            field_data.height,
+            field_data.mine_spec,
+            &field_data.solver_config(),
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("Error: {}", err);
+            exit(1);
+        });
         process_field!(field, &field_data.output);
     } else {
         let field = RandomField::new(field_data.width, field_data.height, field_data.mine_spec)
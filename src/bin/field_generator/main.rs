@@ -2,6 +2,7 @@ mod events;
 mod generation;
 mod generator;
 mod params;
+mod rating;
 mod render;
 mod screen;
 mod tui;
@@ -0,0 +1,127 @@
+use minesweeper_ng_gen::*;
+use std::{fs, io};
+
+pub const RATINGS_FILE: &str = "ratings.csv";
+
+pub struct Rating {
+    pub metrics: BoardMetrics,
+    /// Only rated for no-guess fields, others may need guesses
+    pub difficulty: Option<Difficulty>,
+    /// Clicks of a player who knows what the solver knows, also only for no-guess fields
+    pub clicks: Option<ClickSequence>,
+    /// Where the solver gets stuck, only for fields which aren't no-guess and only if asked for
+    pub ambiguity: Option<AmbiguityReport>,
+}
+
+/// Rates a generated field. All solvers run with `config`, so its budget and cancellation
+/// also bound the rating. Ratings which run out of budget are left out.
+pub fn rate(
+    field: &impl MineSweeperField,
+    config: &SolverConfig,
+    no_guess: bool,
+    with_ambiguity: bool,
+) -> Rating {
+    Rating {
+        metrics: board_metrics(field),
+        difficulty: no_guess
+            .then(|| difficulty(field, config).ok())
+            .flatten()
+            .filter(|difficulty| difficulty.solved),
+        clicks: no_guess.then(|| click_sequence(field, config)).flatten(),
+        ambiguity: (!no_guess && with_ambiguity)
+            .then(|| ambiguity(field, config))
+            .flatten(),
+    }
+}
+
+fn click_sequence(field: &impl MineSweeperField, config: &SolverConfig) -> Option<ClickSequence> {
+    let mut solver = Solver::new(field, config);
+    match solver.solve() {
+        Ok(SolveOutcome::Solved) => Some(solver.get_click_sequence()),
+        _ => None,
+    }
+}
+
+fn ambiguity(field: &impl MineSweeperField, config: &SolverConfig) -> Option<AmbiguityReport> {
+    let mut solver = Solver::new(field, config);
+    match solver.solve() {
+        Ok(SolveOutcome::Stuck) => {
+            Some(solver.get_ambiguity_report()).filter(|report| report.gave_up.is_none())
+        }
+        _ => None,
+    }
+}
+
+pub fn describe_metrics(metrics: &BoardMetrics) -> String {
+    format!(
+        "3BV {}, {} openings (largest {}), {} isolated numbers, ZiNi {}",
+        metrics.bbbv,
+        metrics.openings(),
+        metrics.opening_sizes.iter().max().unwrap_or(&0),
+        metrics.isolated_numbers,
+        metrics.zini
+    )
+}
+
+pub fn describe_clicks(clicks: &ClickSequence) -> String {
+    format!(
+        "{} clicks ({} reveals, {} flags, {} chords)",
+        clicks.len(),
+        clicks.reveals(),
+        clicks.flags(),
+        clicks.chords()
+    )
+}
+
+pub fn describe_difficulty(rating: &Difficulty) -> String {
+    format!(
+        "{} ({:.1}), {} steps, critical path {}",
+        rating.tier, rating.score, rating.steps, rating.critical_path_length
+    )
+}
+
+/// Writes the ratings of a batch as `ratings.csv` into its folder, one row per field id.
+/// The difficulty and click columns stay empty for fields which aren't no-guess,
+/// the guess column for fields the solver finishes and without `--ambiguity`.
+pub fn write_ratings(output: &str, ratings: &mut [(u32, Rating)]) -> io::Result<()> {
+    ratings.sort_by_key(|(id, _)| *id);
+
+    let mut csv = String::from(
+        "id,3bv,openings,isolated_numbers,zini,score,tier,steps,critical_path,single_deductions,sat_components,largest_sat_component,sat_search_nodes,clicks,min_guesses\n",
+    );
+    for (id, rating) in ratings.iter() {
+        let metrics = &rating.metrics;
+        csv.push_str(&format!(
+            "{},{},{},{},{},",
+            id,
+            metrics.bbbv,
+            metrics.openings(),
+            metrics.isolated_numbers,
+            metrics.zini
+        ));
+        match &rating.difficulty {
+            Some(difficulty) => csv.push_str(&format!(
+                "{:.2},{},{},{},{},{},{},{}",
+                difficulty.score,
+                difficulty.tier,
+                difficulty.steps,
+                difficulty.critical_path_length,
+                difficulty.single_deduction_steps,
+                difficulty.sat_components,
+                difficulty.largest_sat_component,
+                difficulty.sat_search_nodes
+            )),
+            None => csv.push_str(",,,,,,,"),
+        }
+        match &rating.clicks {
+            Some(clicks) => csv.push_str(&format!(",{}", clicks.len())),
+            None => csv.push(','),
+        }
+        match &rating.ambiguity {
+            Some(ambiguity) => csv.push_str(&format!(",{}\n", ambiguity.min_guesses())),
+            None => csv.push_str(",\n"),
+        }
+    }
+
+    fs::write(format!("{}/{}", output, RATINGS_FILE), csv)
+}
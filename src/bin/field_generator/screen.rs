@@ -1,4 +1,5 @@
 use super::params::Params;
+use minesweeper_ng_gen::CancellationToken;
 use std::sync::{Arc, atomic::AtomicU32, mpsc};
 use std::time::{Duration, Instant};
 
@@ -19,6 +20,7 @@ pub enum Screen {
         done: Arc<AtomicU32>,
         errors: Arc<AtomicU32>,
         rx: mpsc::Receiver<ProgressEvent>,
+        cancel: CancellationToken,
         start: Instant,
         finished: bool,
         elapsed: Duration,
@@ -61,13 +61,14 @@ fn tui_loop(terminal: &mut ratatui::DefaultTerminal, screen: &mut Screen) -> io:
                                 continue;
                             }
                             let total = params.count_val().unwrap();
-                            let (done, errors, rx) = start_generation(params);
+                            let (done, errors, rx, cancel) = start_generation(params);
                             *screen = Screen::Generating {
                                 params: params.clone(),
                                 total,
                                 done,
                                 errors,
                                 rx,
+                                cancel,
                                 start: Instant::now(),
                                 finished: false,
                                 elapsed: Duration::ZERO,
@@ -81,6 +82,7 @@ fn tui_loop(terminal: &mut ratatui::DefaultTerminal, screen: &mut Screen) -> io:
                 done,
                 total,
                 rx,
+                cancel,
                 finished,
                 elapsed,
                 start,
@@ -106,6 +108,7 @@ fn tui_loop(terminal: &mut ratatui::DefaultTerminal, screen: &mut Screen) -> io:
                     if let Event::Key(KeyEvent { kind, code, .. }) = event::read()? {
                         if kind == KeyEventKind::Press {
                             if *finished || code == KeyCode::Esc {
+                                cancel.cancel();
                                 return Ok(());
                             }
                         }
@@ -154,6 +154,11 @@ fn handle_confirm_submit(app: &mut App, purpose: ConfirmPurpose, idx: usize) {
 }
 
 pub fn handle_play_key(state: &mut PlayState, code: KeyCode) -> AppAction {
+    // Hints only stay until the next key
+    if !state.game_over {
+        state.message = None;
+    }
+
     match code {
         KeyCode::Up => state.move_cursor(0, -1),
         KeyCode::Down => state.move_cursor(0, 1),
@@ -168,6 +173,7 @@ pub fn handle_play_key(state: &mut PlayState, code: KeyCode) -> AppAction {
             }
         }
         KeyCode::Char('f' | 'F') => state.toggle_flag(state.cursor_x, state.cursor_y),
+        KeyCode::Char('h' | 'H') => state.show_hint(),
         KeyCode::Char('r' | 'R') => state.reveal_all(),
         KeyCode::Char('q' | 'Q') | KeyCode::Esc => return AppAction::Quit,
         _ => {}
@@ -74,12 +74,18 @@ pub fn render_play(frame: &mut Frame, state: &PlayState) {
             time_str,
         )),
         Span::styled(
-            "| SPACE: reveal/chord | F: flag | R: reveal all | Q: quit",
+            "| SPACE: reveal/chord | F: flag | H: hint | R: reveal all | Q: quit",
             Style::default().fg(Color::DarkGray),
         ),
     ];
     if let Some(ref msg) = state.message {
-        let color = if state.won { Color::Green } else { Color::Red };
+        let color = if state.won {
+            Color::Green
+        } else if state.game_over {
+            Color::Red
+        } else {
+            Color::Yellow
+        };
         status_spans.push(Span::styled(
             format!(" | {}", msg),
             Style::default().fg(color).bold(),
@@ -1,5 +1,7 @@
 use super::popups::{ConfirmPopup, TextInput};
-use minesweeper_ng_gen::{Cell, DefinedField, MineSweeperField, Mines};
+use minesweeper_ng_gen::{
+    Cell, DefinedField, MineSweeperField, Mines, Solver, SolverConfig, VisibleCell,
+};
 use std::time::{Duration, Instant};
 
 #[derive(Copy, Clone, PartialEq)]
@@ -204,6 +206,81 @@ impl PlayState {
         }
     }
 
+    /// Asks the solver for a deduction using only what the player sees and moves the cursor there.
+    /// The player's flags may be wrong, so they are treated as hidden cells.
+    /// Without a deduction the best guess is shown.
+    pub fn show_hint(&mut self) {
+        if self.game_over {
+            return;
+        }
+
+        let cells: Vec<Vec<VisibleCell>> = (0..self.field.get_width())
+            .map(|x| {
+                (0..self.field.get_height())
+                    .map(|y| match self.state[x as usize][y as usize] {
+                        PlayerCellState::Hidden | PlayerCellState::Flagged => VisibleCell::Hidden,
+                        PlayerCellState::Revealed => {
+                            VisibleCell::Revealed(self.field.get_cell(x, y).get_number())
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut solver =
+            match Solver::from_visible(&cells, self.field.get_mines(), &SolverConfig::default()) {
+                Ok(solver) => solver,
+                Err(err) => {
+                    self.message = Some(format!("No hint: {}", err));
+                    return;
+                }
+            };
+
+        // Deduced mines the player already flagged are no hint, look further with them flagged
+        let hint = loop {
+            let Some(finding) = solver.hint() else {
+                break None;
+            };
+            let unflagged = finding
+                .get_mine_fields()
+                .iter()
+                .find(|&&(x, y)| self.state[x as usize][y as usize] != PlayerCellState::Flagged);
+            match (finding.get_safe_fields().first(), unflagged) {
+                (Some(&position), _) => break Some((position, "safe")),
+                (None, Some(&position)) => break Some((position, "a mine")),
+                (None, None) => {}
+            }
+
+            let mut branch = solver.branch();
+            for &(x, y) in finding.get_mine_fields() {
+                if let Err(err) = branch.assume_mine(x, y) {
+                    self.message = Some(format!("No hint: {}", err));
+                    return;
+                }
+            }
+            solver = branch;
+        };
+
+        self.message = Some(match hint {
+            None => match solver.suggest_guess() {
+                None => "No hint: nothing left to find".into(),
+                Some(guess) => {
+                    (self.cursor_x, self.cursor_y) = guess.position;
+                    format!(
+                        "No safe cell, best guess: ({}, {}) is safe with {:.0}%",
+                        guess.position.0,
+                        guess.position.1,
+                        guess.survival_probability * 100.0
+                    )
+                }
+            },
+            Some((position, kind)) => {
+                (self.cursor_x, self.cursor_y) = position;
+                format!("Hint: ({}, {}) is {}", position.0, position.1, kind)
+            }
+        });
+    }
+
     pub fn toggle_flag(&mut self, x: u32, y: u32) {
         if self.game_over {
             return;
@@ -16,7 +16,7 @@ fn main() {
     //let field: RandomField = RandomField::new(10, 10, Mines::Density(0.24)).unwrap();
     //let field: DefinedField = DefinedField::from_file("generated/testing/benchmarking/31.minesweeper").unwrap();
 
-    //let solved = is_solvable(&field);
+    //let solved = is_solvable(&field, &SolverConfig::default());
     //let field: DefinedField = DefinedField::from_file("generated/testing/extended_box_logic.minesweeper ").unwrap();
 
     //let mut solved = false;
@@ -42,6 +42,8 @@ fn main() {
         }
     };
 
-    info!("Field solved: {:?}", is_solvable(&field));
+    let mut solver = create_solver(&field);
+    info!("Field solved: {:?}", solver.solve());
+    info!("Solver stats:\n{}", solver.get_stats());
     field.show();
 }
@@ -3,8 +3,8 @@ mod noguess_field;
 mod solver;
 
 pub use minesweeper_field::{
-    Cell, DefinedField, FieldError, MineSweeperField, MineSweeperFieldDisplay,
-    MineSweeperFieldFileIO, Mines, RandomField,
+    BoardMetrics, Cell, DefinedField, FieldError, MineSweeperField, MineSweeperFieldDisplay,
+    MineSweeperFieldFileIO, Mines, RandomField, board_metrics,
 };
 pub use noguess_field::NoGuessField;
 
@@ -13,4 +13,10 @@ pub use minesweeper_field::MineSweeperFieldJson;
 #[cfg(feature = "svg")]
 pub use minesweeper_field::{MineSweeperFieldSvg, SVG_Mode};
 
-pub use solver::{Finding, Solver, create_solver, is_solvable};
+pub use solver::{
+    AmbiguityReport, AmbiguousRegion, CancellationToken, ClickAction, ClickSequence,
+    ConstraintCell, DeductionGraph, Dependency, Difficulty, DifficultyTier, Explanation, Finding,
+    GiveUpReason, GuessSuggestion, Rule, SolveOutcome, Solver, SolverBudget, SolverConfig,
+    SolverError, SolverSnapshot, SolverStats, SolverView, SolvingSteps, SolvingStrategy, Strategy,
+    StrategyStats, VisibleCell, create_solver, difficulty, is_solvable, try_solve,
+};
@@ -1,3 +1,4 @@
+use crate::{GiveUpReason, SolverError};
 use std::fmt;
 
 #[derive(Debug)]
@@ -15,6 +16,9 @@ pub enum FieldError {
     IoError(std::io::Error),
     SerializationError(String),
     Deadlock(String),
+    /// The budget of the solver config ran out before a no-guess field was found
+    GaveUp(GiveUpReason),
+    Solver(SolverError),
 }
 
 impl fmt::Display for FieldError {
@@ -43,6 +47,8 @@ impl fmt::Display for FieldError {
                 write!(f, "Serialization error: {}", msg)
             }
             FieldError::Deadlock(msg) => write!(f, "Generation deadlock: {}", msg),
+            FieldError::GaveUp(reason) => write!(f, "Generation gave up: {}", reason),
+            FieldError::Solver(err) => write!(f, "Solver error: {}", err),
         }
     }
 }
@@ -51,6 +57,7 @@ impl std::error::Error for FieldError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             FieldError::IoError(err) => Some(err),
+            FieldError::Solver(err) => Some(err),
             _ => None,
         }
     }
@@ -61,3 +68,9 @@ impl From<std::io::Error> for FieldError {
         FieldError::IoError(err)
     }
 }
+
+impl From<SolverError> for FieldError {
+    fn from(err: SolverError) -> Self {
+        FieldError::Solver(err)
+    }
+}
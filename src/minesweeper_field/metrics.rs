@@ -0,0 +1,318 @@
+use super::{Cell, MineSweeperField};
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+
+/*
+Board metrics:
+
+- An opening is a connected region of empty cells together with the numbers bordering it.
+  A single click on any of its empty cells reveals all of it.
+- An isolated number borders no empty cell and needs its own click.
+- 3BV (Bechtel's Board Benchmark Value) is the minimum number of left clicks to clear the board
+  without flags: one per opening plus one per isolated number.
+- ZiNi estimates the minimum clicks when flagging and chording are allowed. It is computed greedily:
+  as long as some number gains more than it costs, the best one is opened (1 click), its unflagged
+  mines are flagged (1 click each) and it is chorded (1 click), which clears every opening and
+  isolated number around it. Whatever is left costs one click per 3BV.
+  On ties the first number in row-major order is taken, as in the original algorithm.
+*/
+
+/// Classic competitive metrics of a field, see [`board_metrics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardMetrics {
+    pub bbbv: u32,
+    /// Number of cells of every opening, including its bordering numbers, in row-major order of their first cell
+    pub opening_sizes: Vec<u32>,
+    pub isolated_numbers: u32,
+    /// Estimated minimum clicks using flags and chords
+    pub zini: u32,
+}
+
+impl BoardMetrics {
+    pub fn openings(&self) -> usize {
+        self.opening_sizes.len()
+    }
+}
+
+/// Computes 3BV, openings, isolated numbers and ZiNi of a field.
+pub fn board_metrics(field: &impl MineSweeperField) -> BoardMetrics {
+    let board = Board::new(field);
+
+    let isolated_numbers = board.isolated.iter().flatten().filter(|&&i| i).count() as u32;
+    let opening_sizes: Vec<u32> = board
+        .opening_cells
+        .iter()
+        .map(|cells| cells.len() as u32)
+        .collect();
+
+    BoardMetrics {
+        bbbv: opening_sizes.len() as u32 + isolated_numbers,
+        opening_sizes,
+        isolated_numbers,
+        zini: board.zini(),
+    }
+}
+
+struct Board<'a, F: MineSweeperField> {
+    field: &'a F,
+    /// Opening of every empty cell
+    openings: Vec<Vec<Option<usize>>>,
+    /// Empty cells of every opening and the numbers bordering them
+    opening_cells: Vec<Vec<(u32, u32)>>,
+    isolated: Vec<Vec<bool>>,
+}
+
+impl<'a, F: MineSweeperField> Board<'a, F> {
+    fn new(field: &'a F) -> Self {
+        let (width, height) = (field.get_width() as usize, field.get_height() as usize);
+        let mut openings = vec![vec![None; height]; width];
+        let mut opening_cells = Vec::new();
+        // Last opening a border number was added to, numbers can border several openings
+        let mut bordered: Vec<Vec<Option<usize>>> = vec![vec![None; height]; width];
+
+        for (x, y) in field.sorted_fields() {
+            if field.get_cell(x, y) != &Cell::Empty || openings[x as usize][y as usize].is_some() {
+                continue;
+            }
+
+            let opening = opening_cells.len();
+            let mut cells = vec![(x, y)];
+            let mut stack = vec![(x, y)];
+            openings[x as usize][y as usize] = Some(opening);
+            while let Some((cx, cy)) = stack.pop() {
+                for (nx, ny) in field.surrounding_fields(cx, cy, None) {
+                    let (ux, uy) = (nx as usize, ny as usize);
+                    if field.get_cell(nx, ny) == &Cell::Empty {
+                        if openings[ux][uy].is_none() {
+                            openings[ux][uy] = Some(opening);
+                            cells.push((nx, ny));
+                            stack.push((nx, ny));
+                        }
+                    } else if bordered[ux][uy] != Some(opening) {
+                        bordered[ux][uy] = Some(opening);
+                        cells.push((nx, ny));
+                    }
+                }
+            }
+            cells.sort_by_key(|&(x, y)| (y, x));
+            opening_cells.push(cells);
+        }
+
+        let mut isolated = vec![vec![false; height]; width];
+        for (x, y) in field.sorted_fields() {
+            isolated[x as usize][y as usize] = matches!(field.get_cell(x, y), Cell::Number(_))
+                && bordered[x as usize][y as usize].is_none();
+        }
+
+        Board {
+            field,
+            openings,
+            opening_cells,
+            isolated,
+        }
+    }
+
+    fn zini(&self) -> u32 {
+        let (width, height) = (
+            self.field.get_width() as usize,
+            self.field.get_height() as usize,
+        );
+        let mut state = ClickState {
+            opened: vec![vec![false; height]; width],
+            flagged: vec![vec![false; height]; width],
+            opened_openings: vec![false; self.opening_cells.len()],
+        };
+        let mut clicks = 0;
+
+        // Premiums ordered best first, ties in row-major order
+        let mut premiums = vec![vec![0; height]; width];
+        let mut ranking: BTreeSet<(Reverse<i32>, u32, u32)> = BTreeSet::new();
+        for (x, y) in self.field.sorted_fields() {
+            if matches!(self.field.get_cell(x, y), Cell::Number(_)) {
+                let premium = self.premium(&state, x, y);
+                premiums[x as usize][y as usize] = premium;
+                ranking.insert((Reverse(premium), y, x));
+            }
+        }
+
+        while let Some(&(Reverse(premium), y, x)) = ranking.first() {
+            if premium <= 0 {
+                break;
+            }
+
+            clicks += self.cost(&state, x, y) as u32;
+            let mut changed = self.open(&mut state, x, y);
+            for (nx, ny) in self.field.surrounding_fields(x, y, None) {
+                if self.field.get_cell(nx, ny) == &Cell::Mine {
+                    state.flagged[nx as usize][ny as usize] = true;
+                    changed.push((nx, ny));
+                } else {
+                    changed.extend(self.open(&mut state, nx, ny));
+                }
+            }
+
+            // A premium only depends on the number and its neighbours
+            for (cx, cy) in changed {
+                let area = self
+                    .field
+                    .surrounding_fields(cx, cy, None)
+                    .chain([(cx, cy)]);
+                for (nx, ny) in area {
+                    if !matches!(self.field.get_cell(nx, ny), Cell::Number(_)) {
+                        continue;
+                    }
+                    let old = premiums[nx as usize][ny as usize];
+                    let new = self.premium(&state, nx, ny);
+                    if old != new {
+                        ranking.remove(&(Reverse(old), ny, nx));
+                        ranking.insert((Reverse(new), ny, nx));
+                        premiums[nx as usize][ny as usize] = new;
+                    }
+                }
+            }
+        }
+
+        // Everything not cleared by chords is clicked one by one
+        let openings_left = state.opened_openings.iter().filter(|&&o| !o).count() as u32;
+        let isolated_left = self
+            .field
+            .sorted_fields()
+            .filter(|&(x, y)| {
+                self.isolated[x as usize][y as usize] && !state.opened[x as usize][y as usize]
+            })
+            .count() as u32;
+
+        clicks + openings_left + isolated_left
+    }
+
+    /// Clicks for opening a number (if needed), flagging its mines and chording it
+    fn cost(&self, state: &ClickState, x: u32, y: u32) -> i32 {
+        let flags = self
+            .field
+            .surrounding_fields(x, y, None)
+            .filter(|&(nx, ny)| {
+                self.field.get_cell(nx, ny) == &Cell::Mine
+                    && !state.flagged[nx as usize][ny as usize]
+            })
+            .count() as i32;
+
+        flags + 1 + !state.opened[x as usize][y as usize] as i32
+    }
+
+    /// 3BV cleared by opening and chording a number, minus the clicks it takes
+    fn premium(&self, state: &ClickState, x: u32, y: u32) -> i32 {
+        let mut cleared = 0;
+        let mut openings: Vec<usize> = Vec::new();
+
+        let own = (!state.opened[x as usize][y as usize]).then_some((x, y));
+        for (nx, ny) in self.field.surrounding_fields(x, y, None).chain(own) {
+            if state.opened[nx as usize][ny as usize] {
+                continue;
+            }
+            if let Some(opening) = self.openings[nx as usize][ny as usize] {
+                if !openings.contains(&opening) {
+                    openings.push(opening);
+                    cleared += 1;
+                }
+            } else if self.isolated[nx as usize][ny as usize] {
+                cleared += 1;
+            }
+        }
+
+        cleared - self.cost(state, x, y)
+    }
+
+    /// Reveals a cell like a click, empty cells reveal their whole opening.
+    /// Returns the newly opened cells.
+    fn open(&self, state: &mut ClickState, x: u32, y: u32) -> Vec<(u32, u32)> {
+        let cells = match self.openings[x as usize][y as usize] {
+            Some(opening) if !state.opened_openings[opening] => {
+                state.opened_openings[opening] = true;
+                self.opening_cells[opening].clone()
+            }
+            Some(_) => Vec::new(),
+            None => vec![(x, y)],
+        };
+
+        cells
+            .into_iter()
+            .filter(|&(cx, cy)| {
+                !std::mem::replace(&mut state.opened[cx as usize][cy as usize], true)
+            })
+            .collect()
+    }
+}
+
+struct ClickState {
+    opened: Vec<Vec<bool>>,
+    flagged: Vec<Vec<bool>>,
+    opened_openings: Vec<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, Mines};
+
+    fn field(width: u32, height: u32, mines: Vec<(u32, u32)>) -> DefinedField {
+        let mut field = DefinedField::new(width, height, Mines::Count(mines.len() as u32)).unwrap();
+        field.initialize(mines);
+        field
+    }
+
+    #[test]
+    fn test_single_opening() {
+        let metrics = board_metrics(&field(4, 4, vec![(3, 3)]));
+
+        // 12 empty cells and the three 1s around the mine
+        assert_eq!(metrics.opening_sizes, vec![15]);
+        assert_eq!(metrics.isolated_numbers, 0);
+        assert_eq!(metrics.bbbv, 1);
+        assert_eq!(metrics.zini, 1);
+    }
+
+    #[test]
+    fn test_chording_saves_clicks() {
+        // Eight isolated 1s around a single mine
+        let metrics = board_metrics(&field(3, 3, vec![(1, 1)]));
+        assert_eq!(metrics.openings(), 0);
+        assert_eq!(metrics.isolated_numbers, 8);
+        assert_eq!(metrics.bbbv, 8);
+
+        // Open, flag and chord the top 1, then chord the left 1 and click the last corner
+        assert_eq!(metrics.zini, 5);
+    }
+
+    #[test]
+    fn test_separate_openings() {
+        // The 1 below the mine borders no empty cell
+        let metrics = board_metrics(&field(5, 2, vec![(2, 0)]));
+        assert_eq!(metrics.opening_sizes, vec![4, 4]);
+        assert_eq!(metrics.isolated_numbers, 1);
+        assert_eq!(metrics.bbbv, 3);
+
+        // A wall of mines splits the board
+        let metrics = board_metrics(&field(7, 3, vec![(3, 0), (3, 1), (3, 2)]));
+        assert_eq!(metrics.opening_sizes, vec![9, 9]);
+        assert_eq!(metrics.bbbv, 2);
+        assert_eq!(metrics.zini, 2);
+    }
+
+    #[test]
+    fn test_testing_fields() {
+        for path in [
+            "generated/testing/hard.minesweeper",
+            "generated/testing/evil_ng_field.minesweeper",
+        ] {
+            let field = DefinedField::from_file(path).expect("Failed to load testing file");
+            let metrics = board_metrics(&field);
+
+            assert_eq!(
+                metrics.bbbv,
+                metrics.openings() as u32 + metrics.isolated_numbers
+            );
+            assert!(metrics.zini <= metrics.bbbv);
+            assert!(metrics.zini > 0);
+        }
+    }
+}
@@ -4,6 +4,7 @@ mod display;
 mod error;
 mod file_io;
 mod iterators;
+mod metrics;
 mod mines;
 mod random_field;
 mod r#trait;
@@ -19,6 +20,7 @@ pub use display::MineSweeperFieldDisplay;
 pub use error::FieldError;
 pub use file_io::MineSweeperFieldFileIO;
 pub use iterators::{SortedCells, SurroundingCells};
+pub use metrics::{BoardMetrics, board_metrics};
 pub use mines::Mines;
 pub use random_field::RandomField;
 pub use r#trait::MineSweeperField;
@@ -160,6 +160,50 @@ impl CandidatePicker {
             Some(candidates)
         }
     }
+    /// Returns up to `batch_size` (remove, place) candidates for fields where every hidden cell
+    /// is enclosed by flagged mines. A flagged mine of the wall is moved into the island,
+    /// cells far from the opened gap are tried first so the new numbers carry information.
+    /// Returns no candidates when there is no wall mine or no free island cell left to try.
+    pub(super) fn pick_island(
+        field: &DefinedField,
+        failed: &FailedMoves,
+        solver_grid: &Vec<Vec<CellState>>,
+        batch_size: usize,
+    ) -> Vec<((u32, u32), (u32, u32))> {
+        let wall_mines: Vec<(u32, u32)> = field
+            .sorted_fields()
+            .filter(|&(x, y)| {
+                matches!(&solver_grid[x as usize][y as usize], CellState::Flagged(_))
+                    && field.surrounding_fields(x, y, None).any(|(nx, ny)| {
+                        matches!(&solver_grid[nx as usize][ny as usize], CellState::Hidden(_))
+                    })
+            })
+            .collect();
+
+        let targets = all_non_mine_hidden_cells(field, solver_grid);
+
+        let mut candidates = Vec::new();
+
+        'outer: for &remove in &wall_mines {
+            let mut places: Vec<(u32, u32)> = targets
+                .iter()
+                .copied()
+                .filter(|&place| !failed.contains(&(remove, place)))
+                .collect();
+            places.sort_by_key(|&(x, y)| {
+                std::cmp::Reverse(x.abs_diff(remove.0).max(y.abs_diff(remove.1)))
+            });
+
+            for place in places {
+                candidates.push((remove, place));
+                if candidates.len() >= batch_size {
+                    break 'outer;
+                }
+            }
+        }
+
+        candidates
+    }
 }
 
 fn revealed_number_neighbor_count(x: u32, y: u32, grid: &Vec<Vec<CellState>>) -> usize {
@@ -1,6 +1,6 @@
 use super::generator;
 use crate::solver::create_solver;
-use crate::{Cell, DefinedField, FieldError, MineSweeperField, Mines};
+use crate::{Cell, DefinedField, FieldError, MineSweeperField, Mines, SolveOutcome, SolverConfig};
 
 const DEFAULT_BATCH_SIZE: usize = 20;
 
@@ -31,14 +31,23 @@ impl MineSweeperField for NoGuessField {
 
 impl NoGuessField {
     pub fn new(width: u32, height: u32, mines: Mines) -> Result<Self, FieldError> {
-        generator::generate(width, height, mines, DEFAULT_BATCH_SIZE)
+        Self::with_config(width, height, mines, &SolverConfig::default())
+    }
+
+    /// Generates a field which is solvable using only the strategies enabled in `config`.
+    pub fn with_config(
+        width: u32,
+        height: u32,
+        mines: Mines,
+        config: &SolverConfig,
+    ) -> Result<Self, FieldError> {
+        generator::generate(width, height, mines, DEFAULT_BATCH_SIZE, config)
     }
 
     pub fn from_file(file_path: &str) -> Result<NoGuessField, FieldError> {
         let field = DefinedField::from_file(file_path)?;
         let mut solver = create_solver(&field);
-        solver.solve();
-        if !solver.is_solved() {
+        if solver.solve()? != SolveOutcome::Solved {
             return Err(FieldError::InvalidFileData(
                 "field requires guessing and is not a valid no-guess field".into(),
             ));
@@ -46,3 +55,35 @@ impl NoGuessField {
         Ok(NoGuessField(field))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MineSweeperFieldFileIO, SolverError};
+
+    #[test]
+    fn test_from_file_reports_solver_errors() {
+        let mut field = DefinedField::new(5, 2, Mines::Count(1)).unwrap();
+        field.initialize(vec![(4, 0)]);
+        field.set_start_cell(3, 0);
+
+        let path = std::env::temp_dir().join(format!(
+            "ng_{}_test_from_file_reports_solver_errors.minesweeper",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        field.to_file(path).unwrap();
+
+        let result = NoGuessField::from_file(path);
+        let _ = std::fs::remove_file(path);
+
+        assert!(matches!(
+            result,
+            Err(FieldError::Solver(SolverError::InvalidStart {
+                x: 3,
+                y: 0,
+                ..
+            }))
+        ));
+    }
+}
@@ -102,6 +102,11 @@ impl Frontier {
                     }
                 }
 
+                // Numbers fully surrounded by revealed or flagged cells don't form a frontier
+                if hidden_set.is_empty() {
+                    continue;
+                }
+
                 let hidden_cells: Vec<(u32, u32)> = hidden_set.into_iter().collect();
                 let mine_count = hidden_cells
                     .iter()
@@ -2,8 +2,10 @@ use super::NoGuessField;
 use super::candidate::CandidatePicker;
 use super::failed_moves::{FailedDoubleMoves, FailedMoves};
 use super::frontier::Frontier;
-use crate::solver::create_solver;
-use crate::{DefinedField, FieldError, MineSweeperField, Mines, RandomField};
+use crate::{
+    DefinedField, FieldError, GiveUpReason, MineSweeperField, Mines, RandomField, SolveOutcome,
+    Solver, SolverConfig,
+};
 
 use log::{debug, info};
 use rayon::prelude::*;
@@ -13,6 +15,7 @@ pub(super) fn generate(
     height: u32,
     mines: Mines,
     batch_size: usize,
+    config: &SolverConfig,
 ) -> Result<NoGuessField, FieldError> {
     let random_field = RandomField::new(width, height, mines.clone())?;
 
@@ -28,34 +31,61 @@ pub(super) fn generate(
         .collect();
     field.initialize(mine_positions);
 
+    remove_guesses(field, batch_size, config)
+}
+
+/// Relocates mines of `field` until the solver can solve it without guessing.
+/// Gives up once the deadline of the config's budget passes or it gets cancelled.
+/// Layouts which exceed the search node limit count as not solvable and get relocated further.
+pub(super) fn remove_guesses(
+    mut field: DefinedField,
+    batch_size: usize,
+    config: &SolverConfig,
+) -> Result<NoGuessField, FieldError> {
     let mut failed = FailedMoves::new();
     let mut failed_double = FailedDoubleMoves::new();
 
     loop {
-        let mut solver = create_solver(&field);
-        solver.solve();
+        // The candidates below only stop their own solvers, so the whole generation is stopped here
+        config.get_budget().check().map_err(FieldError::GaveUp)?;
 
-        if solver.is_solved() {
-            debug!("Field solved, no guessing required.");
-            return Ok(NoGuessField(field));
+        let mut solver = Solver::new(&field, config);
+
+        match solver.solve()? {
+            SolveOutcome::Solved => {
+                debug!("Field solved, no guessing required.");
+                return Ok(NoGuessField(field));
+            }
+            SolveOutcome::GaveUp(reason) if reason != GiveUpReason::NodeLimit => {
+                return Err(FieldError::GaveUp(reason));
+            }
+            _ => {}
         }
 
-        let grid = solver.get_state_grid().clone();
+        let grid = solver.get_state_grid();
         let frontiers = Frontier::identify_all(&grid, &field);
 
-        if frontiers.is_empty() {
-            info!("{}", solver.format_field_state());
-            return Err(FieldError::Deadlock(
-                "solver stuck with no identifiable frontiers (inaccessible island?)".into(),
-            ));
-        }
-
         let revealed_before = solver.revealed_count();
 
-        // --- Single-move attempt ---
-        if let Some(candidates) =
+        // Without frontiers all hidden cells are enclosed by flagged mines,
+        // so a mine of the surrounding wall gets moved into the island
+        let single_candidates = if frontiers.is_empty() {
+            let candidates = CandidatePicker::pick_island(&field, &failed, &grid, batch_size);
+            if candidates.is_empty() {
+                debug!("{}", solver.format_field_state());
+                info!("Deadlock: {}", solver.get_ambiguity_report());
+                return Err(FieldError::Deadlock(
+                    "inaccessible island could not be opened by relocating its surrounding mines"
+                        .into(),
+                ));
+            }
+            Some(candidates)
+        } else {
             CandidatePicker::pick(&frontiers, &field, &failed, &grid, batch_size)
-        {
+        };
+
+        // --- Single-move attempt ---
+        if let Some(candidates) = single_candidates {
             let best = candidates
                 .into_par_iter()
                 .map(|(remove, place)| {
@@ -63,9 +93,9 @@ pub(super) fn generate(
                     clone.remove_mine(remove.0, remove.1);
                     clone.place_mine(place.0, place.1);
 
-                    let mut s = create_solver(&clone);
-                    s.solve();
-                    let count = s.revealed_count();
+                    let mut s = Solver::new(&clone, config);
+                    // A relocation which breaks the solver is never kept
+                    let count = s.solve().map_or(0, |_| s.revealed_count());
                     (remove, place, count, clone)
                 })
                 .max_by_key(|&(_, _, count, _)| count);
@@ -110,9 +140,8 @@ pub(super) fn generate(
                         clone.remove_mine(r2.0, r2.1);
                         clone.place_mine(p2.0, p2.1);
 
-                        let mut s = create_solver(&clone);
-                        s.solve();
-                        let count = s.revealed_count();
+                        let mut s = Solver::new(&clone, config);
+                        let count = s.solve().map_or(0, |_| s.revealed_count());
                         (r1, p1, r2, p2, count, clone)
                     })
                     .max_by_key(|&(_, _, _, _, count, _)| count);
@@ -141,7 +170,8 @@ pub(super) fn generate(
                 }
             }
             None => {
-                info!("{}", solver.format_field_state());
+                debug!("{}", solver.format_field_state());
+                info!("Deadlock: {}", solver.get_ambiguity_report());
                 return Err(FieldError::Deadlock(
                     "all single and double relocations exhausted — layout cannot be made no-guess"
                         .into(),
@@ -150,3 +180,45 @@ pub(super) fn generate(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::is_solvable;
+
+    #[test]
+    fn test_enclosed_island_gets_opened() {
+        // The bottom right 2x2 island is walled off by mines, so no number ever touches it
+        let mut field = DefinedField::new(5, 5, Mines::Count(6)).unwrap();
+        field.initialize(vec![(2, 2), (3, 2), (4, 2), (2, 3), (2, 4), (4, 4)]);
+        field.set_start_cell(0, 0);
+
+        let config = SolverConfig::default();
+        assert!(!is_solvable(&field, &config));
+
+        let ng_field = remove_guesses(field, DEFAULT_TEST_BATCH_SIZE, &config)
+            .expect("Island should be opened by relocating wall mines");
+
+        assert_eq!(ng_field.get_mines(), 6);
+        assert!(is_solvable(&ng_field, &config));
+    }
+
+    #[test]
+    fn test_honours_budget() {
+        let mut field = DefinedField::new(5, 5, Mines::Count(6)).unwrap();
+        field.initialize(vec![(2, 2), (3, 2), (4, 2), (2, 3), (2, 4), (4, 4)]);
+        field.set_start_cell(0, 0);
+
+        let token = crate::CancellationToken::new();
+        token.cancel();
+        let config = SolverConfig::default()
+            .with_budget(crate::SolverBudget::new().with_cancellation(token));
+
+        assert!(matches!(
+            remove_guesses(field, DEFAULT_TEST_BATCH_SIZE, &config),
+            Err(FieldError::GaveUp(GiveUpReason::Cancelled))
+        ));
+    }
+
+    const DEFAULT_TEST_BATCH_SIZE: usize = 16;
+}
@@ -0,0 +1,457 @@
+use super::strategy::sat_solver::{
+    Constraint, build_constraints, enumerate_solutions_within, find_independent_components,
+};
+use super::{CellState, GiveUpReason, Solver, SolverBudget};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/*
+Ambiguity report:
+
+A stuck position is split into the independent components of the SAT strategy (regions),
+each region is judged on its own, the mine count linking them is ignored apart from
+limiting every region to the remaining mines.
+- layouts: every assignment of the region consistent with its numbers, counted like the probabilities
+- pure 50/50: exactly two layouts which disagree on every cell, and no other hidden cell borders the region,
+  so nothing revealed elsewhere can ever decide it
+- minimum guesses: the region is played as a game over its explicit layouts.
+  A guess reveals a cell which is a mine in some layout, the layouts where it is a mine are dropped,
+  as the guess has to survive. If all hidden neighbours of the cell lie inside the region,
+  its number splits the remaining layouts further. Cells which are safe in every remaining layout
+  are revealed for free. The region is finished once a single layout remains,
+  the minimum is taken over the guessed cells and the maximum over what they show.
+  Only regions with at most MAX_PLANNED_LAYOUTS layouts are planned this way.
+Hidden cells outside of all regions are only counted, nothing is known about them.
+Counting and planning run within the budget of the solver config. Regions whose layouts
+couldn't be counted are left out and regions which couldn't be planned stay unplanned,
+the report then says why it is incomplete.
+*/
+
+/// Regions with more layouts aren't planned, the layouts of a region are a bit set
+const MAX_PLANNED_LAYOUTS: usize = 64;
+
+/// One independent region of a stuck position, see [`Solver::get_ambiguity_report`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmbiguousRegion {
+    /// Hidden cells of the region, sorted
+    pub cells: Vec<(u32, u32)>,
+    /// Layouts of the region consistent with its bordering numbers
+    pub layouts: f64,
+    /// Two layouts differing in every cell, which no other cell can tell apart
+    pub fifty_fifty: bool,
+    /// Fewest guesses which finish the region whatever its layout, as long as they survive.
+    /// `None` if the region has too many layouts to plan or the budget ran out.
+    pub min_guesses: Option<u32>,
+}
+
+/// The ambiguous regions of a stuck position, see [`Solver::get_ambiguity_report`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AmbiguityReport {
+    /// Regions with more than one layout, sorted by their first cell
+    pub regions: Vec<AmbiguousRegion>,
+    /// Hidden cells which border no revealed number
+    pub unconstrained_cells: usize,
+    /// Set if the budget ran out, regions may then be missing or unplanned
+    pub gave_up: Option<GiveUpReason>,
+}
+
+impl AmbiguityReport {
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty() && self.unconstrained_cells == 0
+    }
+
+    pub fn fifty_fifties(&self) -> usize {
+        self.regions
+            .iter()
+            .filter(|region| region.fifty_fifty)
+            .count()
+    }
+
+    /// Guesses needed for the planned regions, a lower bound if any region couldn't be planned.
+    /// Unconstrained cells aren't counted.
+    pub fn min_guesses(&self) -> u32 {
+        self.regions
+            .iter()
+            .filter_map(|region| region.min_guesses)
+            .sum()
+    }
+}
+
+impl fmt::Display for AmbiguityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ambiguous regions ({} pure 50/50), {} unconstrained cells, guesses needed: at least {}",
+            self.regions.len(),
+            self.fifty_fifties(),
+            self.unconstrained_cells,
+            self.min_guesses()
+        )?;
+        if let Some(reason) = self.gave_up {
+            write!(f, ", incomplete: {}", reason)?;
+        }
+        for region in &self.regions {
+            write!(
+                f,
+                "\n  {} cells at {:?}: {} layouts",
+                region.cells.len(),
+                region.cells[0],
+                region.layouts
+            )?;
+            if region.fifty_fifty {
+                write!(f, ", pure 50/50")?;
+            }
+            match region.min_guesses {
+                Some(guesses) => write!(f, ", guesses needed: {}", guesses)?,
+                None if region.layouts > MAX_PLANNED_LAYOUTS as f64 => {
+                    write!(f, ", too many layouts to plan")?
+                }
+                None => write!(f, ", not planned")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Solver {
+    /// How the current position is ambiguous, meant for the position a stuck [`Solver::solve`] leaves behind.
+    /// Empty once the field is solved.
+    pub fn get_ambiguity_report(&self) -> AmbiguityReport {
+        if self.is_solved() {
+            return AmbiguityReport::default();
+        }
+
+        let remaining_mines = self.get_remaining_mines();
+        let budget = self.config.get_budget();
+        let mut gave_up = None;
+        let components = find_independent_components(self);
+        let border: HashSet<(u32, u32)> = components.iter().flatten().copied().collect();
+        let unconstrained_cells = self
+            .sorted_fields()
+            .filter(|&(x, y)| matches!(self.get_state(x, y), CellState::Hidden(_)))
+            .filter(|field| !border.contains(field))
+            .count();
+
+        let mut regions: Vec<AmbiguousRegion> = components
+            .into_iter()
+            .filter_map(|mut cells| {
+                cells.sort();
+                let constraints = build_constraints(self, &cells);
+                let solutions = enumerate_solutions_within(
+                    &cells,
+                    &constraints,
+                    remaining_mines,
+                    budget,
+                    &self.search_counters,
+                );
+                let layouts: f64 = match solutions {
+                    Ok(solutions) => solutions.solution_counts.iter().sum(),
+                    Err(reason) => {
+                        gave_up.get_or_insert(reason);
+                        return None;
+                    }
+                };
+                if layouts <= 1.0 {
+                    return None;
+                }
+
+                let explicit = (layouts <= MAX_PLANNED_LAYOUTS as f64)
+                    .then(|| list_layouts(&cells, &constraints, remaining_mines));
+                let fifty_fifty = explicit.as_ref().is_some_and(|explicit| {
+                    explicit.len() == 2
+                        && explicit[0].iter().zip(&explicit[1]).all(|(a, b)| a != b)
+                        && self.is_enclosed(&cells)
+                });
+                let min_guesses = explicit.and_then(|explicit| {
+                    Planner::new(self, &cells, explicit, budget.clone())
+                        .min_guesses()
+                        .map_err(|reason| gave_up.get_or_insert(reason))
+                        .ok()
+                });
+
+                Some(AmbiguousRegion {
+                    cells,
+                    layouts,
+                    fifty_fifty,
+                    min_guesses,
+                })
+            })
+            .collect();
+        regions.sort_by_key(|region| region.cells[0]);
+
+        AmbiguityReport {
+            regions,
+            unconstrained_cells,
+            gave_up,
+        }
+    }
+
+    /// True if no hidden cell outside of `cells` borders one of them
+    fn is_enclosed(&self, cells: &[(u32, u32)]) -> bool {
+        cells.iter().all(|&(x, y)| {
+            self.get_surrounding_unrevealed(x, y)
+                .iter()
+                .all(|neighbour| cells.contains(neighbour))
+        })
+    }
+}
+
+/// Every layout of `cells` satisfying `constraints` with at most `max_mines` mines, as mine flags
+fn list_layouts(
+    cells: &[(u32, u32)],
+    constraints: &[Constraint],
+    max_mines: u32,
+) -> Vec<Vec<bool>> {
+    let index: HashMap<(u32, u32), usize> = cells
+        .iter()
+        .enumerate()
+        .map(|(i, &cell)| (cell, i))
+        .collect();
+    let constraints: Vec<(Vec<usize>, u32)> = constraints
+        .iter()
+        .map(|c| (c.fields.iter().map(|f| index[f]).collect(), c.mine_count))
+        .collect();
+
+    fn extend(
+        layout: &mut Vec<bool>,
+        size: usize,
+        constraints: &[(Vec<usize>, u32)],
+        max_mines: u32,
+        layouts: &mut Vec<Vec<bool>>,
+    ) {
+        let assigned = layout.len();
+        let fits = constraints.iter().all(|(fields, mine_count)| {
+            let mines = fields
+                .iter()
+                .filter(|&&i| i < assigned && layout[i])
+                .count() as u32;
+            let open = fields.iter().filter(|&&i| i >= assigned).count() as u32;
+            mines <= *mine_count && mines + open >= *mine_count
+        });
+        if !fits || layout.iter().filter(|&&mine| mine).count() as u32 > max_mines {
+            return;
+        }
+        if assigned == size {
+            layouts.push(layout.clone());
+            return;
+        }
+        for mine in [false, true] {
+            layout.push(mine);
+            extend(layout, size, constraints, max_mines, layouts);
+            layout.pop();
+        }
+    }
+
+    let mut layouts = Vec::new();
+    extend(
+        &mut Vec::new(),
+        cells.len(),
+        &constraints,
+        max_mines,
+        &mut layouts,
+    );
+    layouts
+}
+
+/// Guess planning over the explicit layouts of one region, sets of layouts are bit masks
+struct Planner {
+    layouts: Vec<Vec<bool>>,
+    /// Per cell: indices of its hidden neighbours in the region, `None` if it has others
+    neighbours: Vec<Option<Vec<usize>>>,
+    memo: HashMap<u64, u32>,
+    budget: SolverBudget,
+}
+
+impl Planner {
+    fn new(
+        solver: &Solver,
+        cells: &[(u32, u32)],
+        layouts: Vec<Vec<bool>>,
+        budget: SolverBudget,
+    ) -> Self {
+        let neighbours = cells
+            .iter()
+            .map(|&(x, y)| {
+                solver
+                    .get_surrounding_unrevealed(x, y)
+                    .iter()
+                    .map(|neighbour| cells.iter().position(|cell| cell == neighbour))
+                    .collect()
+            })
+            .collect();
+
+        Planner {
+            layouts,
+            neighbours,
+            memo: HashMap::new(),
+            budget,
+        }
+    }
+
+    fn min_guesses(&mut self) -> Result<u32, GiveUpReason> {
+        let all = match self.layouts.len() {
+            64 => u64::MAX,
+            n => (1 << n) - 1,
+        };
+        self.solve(all)
+    }
+
+    fn solve(&mut self, layouts: u64) -> Result<u32, GiveUpReason> {
+        if layouts.count_ones() <= 1 {
+            return Ok(0);
+        }
+        if let Some(&guesses) = self.memo.get(&layouts) {
+            return Ok(guesses);
+        }
+        self.budget.check()?;
+
+        let mut best = u32::MAX;
+        for cell in 0..self.neighbours.len() {
+            // Layouts where the cell is safe, grouped by the number it shows
+            let mut outcomes: HashMap<Option<usize>, u64> = HashMap::new();
+            let mut risky = false;
+            for layout in self.members(layouts) {
+                if self.layouts[layout][cell] {
+                    risky = true;
+                    continue;
+                }
+                let number = self.neighbours[cell].as_ref().map(|neighbours| {
+                    neighbours
+                        .iter()
+                        .filter(|&&n| self.layouts[layout][n])
+                        .count()
+                });
+                *outcomes.entry(number).or_default() |= 1 << layout;
+            }
+            if outcomes.is_empty() || outcomes.values().any(|&outcome| outcome == layouts) {
+                // Certain mine, or a safe cell which tells nothing
+                continue;
+            }
+
+            let mut worst = 0;
+            for outcome in outcomes.into_values() {
+                worst = worst.max(self.solve(outcome)?);
+                if worst + risky as u32 >= best {
+                    break;
+                }
+            }
+            best = best.min(worst + risky as u32);
+        }
+
+        self.memo.insert(layouts, best);
+        Ok(best)
+    }
+
+    fn members(&self, layouts: u64) -> Vec<usize> {
+        (0..self.layouts.len())
+            .filter(|&layout| layouts & (1 << layout) != 0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CancellationToken, DefinedField, Mines, SolveOutcome, SolverConfig, create_solver,
+    };
+
+    fn stuck_report(
+        width: u32,
+        height: u32,
+        mines: Vec<(u32, u32)>,
+        start: (u32, u32),
+    ) -> AmbiguityReport {
+        let mut field = DefinedField::new(width, height, Mines::Count(mines.len() as u32)).unwrap();
+        field.initialize(mines);
+        field.set_start_cell(start.0, start.1);
+        let mut solver = create_solver(&field);
+        assert_eq!(solver.solve(), Ok(SolveOutcome::Stuck));
+        solver.get_ambiguity_report()
+    }
+
+    #[test]
+    fn test_pure_fifty_fifty() {
+        // The 1s of the third column see both cells of the last column, one of them is the mine
+        let report = stuck_report(5, 2, vec![(4, 0)], (0, 0));
+
+        assert_eq!(report.regions.len(), 1);
+        let region = &report.regions[0];
+        assert_eq!(region.cells, vec![(4, 0), (4, 1)]);
+        assert_eq!(region.layouts, 2.0);
+        assert!(region.fifty_fifty);
+        assert_eq!(region.min_guesses, Some(1));
+        assert_eq!(report.unconstrained_cells, 0);
+        assert_eq!(report.min_guesses(), 1);
+        assert!(report.to_string().contains("pure 50/50"));
+    }
+
+    #[test]
+    fn test_two_fifty_fifties() {
+        // Two separate 50/50s at both ends of a 9x2 field
+        let report = stuck_report(9, 2, vec![(0, 0), (8, 1)], (4, 0));
+
+        assert_eq!(report.regions.len(), 2);
+        assert_eq!(report.fifty_fifties(), 2);
+        assert_eq!(report.min_guesses(), 2);
+    }
+
+    #[test]
+    fn test_report_within_budget() {
+        let mut field = DefinedField::new(5, 2, Mines::Count(1)).unwrap();
+        field.initialize(vec![(4, 0)]);
+        field.set_start_cell(0, 0);
+
+        let token = CancellationToken::new();
+        let config = SolverConfig::default()
+            .with_budget(SolverBudget::new().with_cancellation(token.clone()));
+        let mut solver = Solver::new(&field, &config);
+        assert_eq!(solver.solve(), Ok(SolveOutcome::Stuck));
+
+        // The 50/50 is still found, but not planned any more
+        token.cancel();
+        let report = solver.get_ambiguity_report();
+        assert_eq!(report.gave_up, Some(GiveUpReason::Cancelled));
+        assert_eq!(report.regions.len(), 1);
+        assert_eq!(report.regions[0].min_guesses, None);
+        assert!(report.to_string().contains("incomplete"));
+    }
+
+    #[test]
+    fn test_guess_reveals_number() {
+        // One mine among cells a, b and c
+        let layouts = vec![
+            vec![true, false, false],
+            vec![false, true, false],
+            vec![false, false, true],
+        ];
+
+        // Without numbers, guessing a leaves b or c
+        let mut blind = Planner {
+            layouts: layouts.clone(),
+            neighbours: vec![None; 3],
+            memo: HashMap::new(),
+            budget: SolverBudget::default(),
+        };
+        assert_eq!(blind.min_guesses(), Ok(2));
+
+        // If a borders b and nothing else hidden, its number tells b and c apart
+        let mut informed = Planner {
+            layouts,
+            neighbours: vec![Some(vec![1]), None, None],
+            memo: HashMap::new(),
+            budget: SolverBudget::default(),
+        };
+        assert_eq!(informed.min_guesses(), Ok(1));
+    }
+
+    #[test]
+    fn test_solved_field_has_no_ambiguity() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+        let mut solver = create_solver(&field);
+        solver.solve().unwrap();
+
+        assert!(solver.get_ambiguity_report().is_empty());
+    }
+}
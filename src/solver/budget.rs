@@ -0,0 +1,162 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/*
+Solver budgets:
+
+The SAT search is exponential in the worst case, a single pathological component can keep the solver
+busy for a very long time. A budget bounds a solving run by
+- a wall-clock deadline, checked before every strategy and regularly during the SAT search
+- a maximum number of search nodes per component, checked during the SAT search
+- a cancellation token, shared between threads, checked like the deadline
+Running out of budget is not a contradiction and doesn't prove the field needs a guess,
+so the run ends with `SolveOutcome::GaveUp` instead of `Stuck`.
+*/
+
+/// How often the SAT search checks the clock and the cancellation token
+const CHECK_INTERVAL: usize = 256;
+
+/// Shared flag which stops every solver using it, e.g. from another thread.
+/// Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Why a solver ran out of budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GiveUpReason {
+    Deadline,
+    /// A SAT component needed more search nodes than allowed
+    NodeLimit,
+    Cancelled,
+}
+
+impl fmt::Display for GiveUpReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GiveUpReason::Deadline => write!(f, "deadline reached"),
+            GiveUpReason::NodeLimit => write!(f, "search node limit reached"),
+            GiveUpReason::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// Limits of a solving run, unlimited by default. Set on [`SolverConfig::with_budget`](super::SolverConfig::with_budget).
+#[derive(Debug, Clone, Default)]
+pub struct SolverBudget {
+    deadline: Option<Instant>,
+    max_search_nodes: Option<usize>,
+    cancellation: Option<CancellationToken>,
+}
+
+impl SolverBudget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Deadline `timeout` from now
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Maximum search nodes the SAT search may explore per component
+    pub fn with_max_search_nodes(mut self, nodes: usize) -> Self {
+        self.max_search_nodes = Some(nodes);
+        self
+    }
+
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    pub fn get_deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub fn get_max_search_nodes(&self) -> Option<usize> {
+        self.max_search_nodes
+    }
+
+    pub fn get_cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    /// Errors if the run was cancelled or the deadline has passed
+    pub fn check(&self) -> Result<(), GiveUpReason> {
+        if self
+            .cancellation
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+        {
+            return Err(GiveUpReason::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(GiveUpReason::Deadline);
+        }
+        Ok(())
+    }
+
+    /// Check during a search which has explored `nodes` nodes so far
+    pub(crate) fn check_search(&self, nodes: usize) -> Result<(), GiveUpReason> {
+        if self.max_search_nodes.is_some_and(|max| nodes > max) {
+            return Err(GiveUpReason::NodeLimit);
+        }
+        if nodes.is_multiple_of(CHECK_INTERVAL) {
+            self.check()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_by_default() {
+        let budget = SolverBudget::default();
+        assert_eq!(budget.check(), Ok(()));
+        assert_eq!(budget.check_search(usize::MAX), Ok(()));
+    }
+
+    #[test]
+    fn test_limits() {
+        let token = CancellationToken::new();
+        let budget = SolverBudget::new()
+            .with_max_search_nodes(10)
+            .with_cancellation(token.clone());
+
+        assert_eq!(budget.check_search(10), Ok(()));
+        assert_eq!(budget.check_search(11), Err(GiveUpReason::NodeLimit));
+
+        token.cancel();
+        assert_eq!(budget.check(), Err(GiveUpReason::Cancelled));
+
+        let expired = SolverBudget::new().with_deadline(Instant::now());
+        assert_eq!(expired.check(), Err(GiveUpReason::Deadline));
+    }
+}
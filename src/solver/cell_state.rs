@@ -3,7 +3,8 @@ use colored::{ColoredString, Colorize};
 
 #[derive(Clone)]
 pub(crate) enum CellState {
-    Hidden(Cell),
+    /// The content is only known when the solver was built from a whole field
+    Hidden(Option<Cell>),
     Revealed(Cell),
     Flagged(Cell),
 }
@@ -16,12 +17,4 @@ impl CellState {
             CellState::Flagged(_cell) => "F".red().bold(),
         }
     }
-
-    pub fn get_cell(&self) -> &Cell {
-        match self {
-            CellState::Hidden(cell) => cell,
-            CellState::Revealed(cell) => cell,
-            CellState::Flagged(cell) => cell,
-        }
-    }
 }
@@ -0,0 +1,472 @@
+use super::{CellState, Solver};
+use crate::Cell;
+use std::collections::HashSet;
+
+/*
+Click sequences:
+
+The solving steps only say which cells are safe or mines. To turn them into player actions they are
+played on a player board, which follows the rules of the interactive player:
+- a left click reveals a hidden cell, empty cells open their neighbours recursively
+- a chord on a revealed number reveals its hidden neighbours if the flags around it match the number,
+  or flags all of them if the number needs every hidden neighbour as a mine
+The player starts with the start cell. A step becomes known once every cell it depends on in the
+deduction graph is revealed (or a known mine), its safe cells and mines are known from then on.
+While known safe cells are hidden, the cheapest way to open them is chosen greedily:
+- a chord is used when it clears more cells than the clicks it costs (placing missing flags + the chord),
+  all hidden neighbours of the number have to be known by then
+- otherwise a known safe cell is clicked: empty cells first as they open more, numbers bordering
+  a hidden empty cell last as they may still be opened by it
+Flags are only placed when a chord needs them. When a number needs all its hidden neighbours as mines,
+a chord on it flags all of them with one click.
+The result is a short sequence for a player who only knows what the solver knew, not a proven minimum.
+*/
+
+/// A single player action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickAction {
+    /// Left click on a hidden cell
+    Reveal { x: u32, y: u32 },
+    /// Right click on a hidden cell
+    Flag { x: u32, y: u32 },
+    /// Chord on a revealed number
+    Chord { x: u32, y: u32 },
+}
+
+/// Player actions which solve a board, see [`Solver::get_click_sequence`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClickSequence {
+    actions: Vec<ClickAction>,
+}
+
+impl ClickSequence {
+    pub fn get_actions(&self) -> &Vec<ClickAction> {
+        &self.actions
+    }
+
+    /// Total number of clicks
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    pub fn reveals(&self) -> usize {
+        self.count(|action| matches!(action, ClickAction::Reveal { .. }))
+    }
+
+    pub fn flags(&self) -> usize {
+        self.count(|action| matches!(action, ClickAction::Flag { .. }))
+    }
+
+    pub fn chords(&self) -> usize {
+        self.count(|action| matches!(action, ClickAction::Chord { .. }))
+    }
+
+    fn count(&self, filter: impl Fn(&ClickAction) -> bool) -> usize {
+        self.actions.iter().filter(|action| filter(action)).count()
+    }
+
+    /// The sequence as JSON: `{"clicks": n, "actions": [{"action": "reveal", "x": 0, "y": 0}, ...]}`
+    #[cfg(feature = "json")]
+    pub fn as_json(&self) -> String {
+        let actions: Vec<serde_json::Value> = self
+            .actions
+            .iter()
+            .map(|action| {
+                let (name, x, y) = match *action {
+                    ClickAction::Reveal { x, y } => ("reveal", x, y),
+                    ClickAction::Flag { x, y } => ("flag", x, y),
+                    ClickAction::Chord { x, y } => ("chord", x, y),
+                };
+                serde_json::json!({ "action": name, "x": x, "y": y })
+            })
+            .collect();
+
+        let json = serde_json::json!({
+            "clicks": self.len(),
+            "reveals": self.reveals(),
+            "flags": self.flags(),
+            "chords": self.chords(),
+            "actions": actions,
+        });
+
+        serde_json::to_string_pretty(&json).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlayerCell {
+    Hidden,
+    Flagged,
+    Revealed,
+}
+
+/// The board as a player sees it while replaying the solving steps
+struct PlayerBoard<'a> {
+    solver: &'a Solver,
+    cells: Vec<Vec<PlayerCell>>,
+}
+
+impl<'a> PlayerBoard<'a> {
+    fn new(solver: &'a Solver) -> Self {
+        PlayerBoard {
+            solver,
+            cells: vec![vec![PlayerCell::Hidden; solver.height as usize]; solver.width as usize],
+        }
+    }
+
+    fn get(&self, x: u32, y: u32) -> PlayerCell {
+        self.cells[x as usize][y as usize]
+    }
+
+    /// The content of a cell as far as the solver knows it
+    fn content(&self, x: u32, y: u32) -> Option<Cell> {
+        match self.solver.get_state(x, y) {
+            CellState::Hidden(cell) => cell.clone(),
+            CellState::Revealed(cell) => Some(cell.clone()),
+            CellState::Flagged(_) => Some(Cell::Mine),
+        }
+    }
+
+    fn number(&self, x: u32, y: u32) -> u8 {
+        self.content(x, y).map_or(0, |cell| cell.get_number())
+    }
+
+    fn neighbours(&self, x: u32, y: u32, state: PlayerCell) -> Vec<(u32, u32)> {
+        self.solver
+            .surrounding_fields(x, y, None)
+            .filter(|&(nx, ny)| self.get(nx, ny) == state)
+            .collect()
+    }
+
+    fn apply(&mut self, action: ClickAction) {
+        match action {
+            ClickAction::Reveal { x, y } => self.reveal(x, y),
+            ClickAction::Flag { x, y } => {
+                if self.get(x, y) == PlayerCell::Hidden {
+                    self.cells[x as usize][y as usize] = PlayerCell::Flagged;
+                }
+            }
+            ClickAction::Chord { x, y } => {
+                let required = self.number(x, y) as usize;
+                let hidden = self.neighbours(x, y, PlayerCell::Hidden);
+                let flags = self.neighbours(x, y, PlayerCell::Flagged).len();
+
+                if !hidden.is_empty() && hidden.len() + flags == required {
+                    for (nx, ny) in hidden {
+                        self.cells[nx as usize][ny as usize] = PlayerCell::Flagged;
+                    }
+                } else if flags == required {
+                    for (nx, ny) in hidden {
+                        self.reveal(nx, ny);
+                    }
+                }
+            }
+        }
+    }
+
+    fn reveal(&mut self, x: u32, y: u32) {
+        let mut stack = vec![(x, y)];
+        while let Some((cx, cy)) = stack.pop() {
+            if self.get(cx, cy) != PlayerCell::Hidden {
+                continue;
+            }
+
+            self.cells[cx as usize][cy as usize] = PlayerCell::Revealed;
+            if self.content(cx, cy) == Some(Cell::Empty) {
+                stack.extend(self.neighbours(cx, cy, PlayerCell::Hidden));
+            }
+        }
+    }
+}
+
+impl Solver {
+    /// Converts the solving steps made so far into player actions.
+    /// Replaying them on the field opens everything the solver revealed, without ever hitting a mine.
+    pub fn get_click_sequence(&self) -> ClickSequence {
+        let graph = self.get_deduction_graph();
+        let mut board = PlayerBoard::new(self);
+        let mut sequence = ClickSequence::default();
+        let mut known = vec![false; graph.len()];
+        let mut known_safe: HashSet<(u32, u32)> = HashSet::new();
+        let mut known_mines: HashSet<(u32, u32)> = HashSet::new();
+
+        // The start cell is the only cell known to be safe before the first click
+        if !self.solving_steps.is_empty() {
+            let (x, y) = self.start_cell;
+            board.apply(ClickAction::Reveal { x, y });
+            sequence.actions.push(ClickAction::Reveal { x, y });
+        }
+
+        loop {
+            // Steps become known once the player sees every cell they depend on
+            let mut changed = true;
+            while changed {
+                changed = false;
+                for step in 0..graph.len() {
+                    let seen = graph.get_dependencies(step).iter().all(|dependency| {
+                        let (x, y) = dependency.cell;
+                        board.get(x, y) == PlayerCell::Revealed
+                            || (known[dependency.step] && known_mines.contains(&(x, y)))
+                    });
+                    if known[step] || !seen {
+                        continue;
+                    }
+
+                    let finding = &self.solving_steps[step];
+                    known[step] = true;
+                    known_safe.extend(finding.get_safe_fields());
+                    known_safe.extend(finding.get_recursive_revelations().iter().flatten());
+                    known_mines.extend(finding.get_mine_fields());
+                    changed = true;
+                }
+            }
+
+            let mut pending: Vec<(u32, u32)> = known_safe
+                .iter()
+                .copied()
+                .filter(|&(x, y)| board.get(x, y) == PlayerCell::Hidden)
+                .collect();
+            if pending.is_empty() {
+                break;
+            }
+            pending.sort_by_key(|&(x, y)| (y, x));
+
+            let actions = match best_chord(&board, &pending, &known_safe, &known_mines) {
+                Some((x, y)) => chord_actions(&board, x, y),
+                None => {
+                    let (x, y) = pending
+                        .iter()
+                        .copied()
+                        .min_by_key(|&(x, y)| click_priority(&board, x, y))
+                        .unwrap();
+                    vec![ClickAction::Reveal { x, y }]
+                }
+            };
+
+            for action in actions {
+                board.apply(action);
+                sequence.actions.push(action);
+            }
+        }
+
+        sequence
+    }
+}
+
+/// Empty cells open the most, numbers bordering a hidden empty cell can wait for it to open
+fn click_priority(board: &PlayerBoard, x: u32, y: u32) -> u8 {
+    if board.content(x, y) == Some(Cell::Empty) {
+        0
+    } else if free_later(board, x, y) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Whether a hidden number is revealed anyway once a neighbouring empty cell is opened
+fn free_later(board: &PlayerBoard, x: u32, y: u32) -> bool {
+    board.content(x, y) != Some(Cell::Empty)
+        && board
+            .neighbours(x, y, PlayerCell::Hidden)
+            .into_iter()
+            .any(|(nx, ny)| board.content(nx, ny) == Some(Cell::Empty))
+}
+
+/// The revealed number whose chord saves the most clicks, if any saves clicks at all
+fn best_chord(
+    board: &PlayerBoard,
+    pending: &[(u32, u32)],
+    known_safe: &HashSet<(u32, u32)>,
+    known_mines: &HashSet<(u32, u32)>,
+) -> Option<(u32, u32)> {
+    let mut candidates: Vec<(u32, u32)> = pending
+        .iter()
+        .flat_map(|&(x, y)| board.neighbours(x, y, PlayerCell::Revealed))
+        .collect();
+    candidates.sort_by_key(|&(x, y)| (y, x));
+    candidates.dedup();
+
+    let mut best = None;
+    let mut best_premium = 0;
+    for (x, y) in candidates {
+        let hidden = board.neighbours(x, y, PlayerCell::Hidden);
+        if !hidden
+            .iter()
+            .all(|cell| known_safe.contains(cell) || known_mines.contains(cell))
+        {
+            continue;
+        }
+
+        let missing_flags = hidden.iter().filter(|c| known_mines.contains(c)).count();
+        // Cells which would be revealed for free by another click don't count
+        let cleared = hidden
+            .iter()
+            .filter(|&&(nx, ny)| known_safe.contains(&(nx, ny)) && !free_later(board, nx, ny))
+            .count();
+        if cleared == 0 {
+            continue;
+        }
+
+        let premium = cleared as i32 - missing_flags as i32 - 1;
+        if premium > best_premium {
+            best_premium = premium;
+            best = Some((x, y));
+        }
+    }
+
+    best
+}
+
+/// Flags the mines around a number and chords it
+fn chord_actions(board: &PlayerBoard, x: u32, y: u32) -> Vec<ClickAction> {
+    let mut board = PlayerBoard {
+        solver: board.solver,
+        cells: board.cells.clone(),
+    };
+    let mut actions = Vec::new();
+
+    let mines = |board: &PlayerBoard| -> Vec<(u32, u32)> {
+        board
+            .neighbours(x, y, PlayerCell::Hidden)
+            .into_iter()
+            .filter(|&(nx, ny)| board.content(nx, ny) == Some(Cell::Mine))
+            .collect()
+    };
+
+    while let Some(&(mx, my)) = mines(&board).first() {
+        // A number which needs all of its hidden neighbours flags them with one chord
+        let flagging_chord = board
+            .neighbours(mx, my, PlayerCell::Revealed)
+            .into_iter()
+            .find(|&(nx, ny)| {
+                let hidden = board.neighbours(nx, ny, PlayerCell::Hidden).len();
+                let flags = board.neighbours(nx, ny, PlayerCell::Flagged).len();
+                hidden >= 2 && hidden + flags == board.number(nx, ny) as usize
+            });
+
+        let action = match flagging_chord {
+            Some((nx, ny)) => ClickAction::Chord { x: nx, y: ny },
+            None => ClickAction::Flag { x: mx, y: my },
+        };
+        board.apply(action);
+        actions.push(action);
+    }
+
+    actions.push(ClickAction::Chord { x, y });
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, MineSweeperField, Mines, SolverConfig, board_metrics};
+
+    /// Replays the sequence and checks that it clears the field without revealing a mine
+    fn replay(solver: &Solver, sequence: &ClickSequence) {
+        let mut board = PlayerBoard::new(solver);
+        for &action in sequence.get_actions() {
+            board.apply(action);
+        }
+
+        for x in 0..solver.width {
+            for y in 0..solver.height {
+                let mine = board.content(x, y) == Some(Cell::Mine);
+                assert_eq!(board.get(x, y) == PlayerCell::Revealed, !mine);
+            }
+        }
+    }
+
+    fn solved(field: &impl MineSweeperField) -> Solver {
+        let mut solver = Solver::new(field, &SolverConfig::default());
+        solver.solve().unwrap();
+        assert!(solver.is_solved());
+        solver
+    }
+
+    #[test]
+    fn test_chording_saves_clicks() {
+        // An opening on the left, the numbers between and right of the mines only border each other
+        let mut field = DefinedField::new(7, 3, Mines::Count(2)).unwrap();
+        field.initialize(vec![(3, 1), (6, 1)]);
+        field.set_start_cell(0, 1);
+
+        let solver = solved(&field);
+        let sequence = solver.get_click_sequence();
+        replay(&solver, &sequence);
+
+        assert_eq!(
+            sequence.get_actions()[0],
+            ClickAction::Reveal { x: 0, y: 1 }
+        );
+        assert!(sequence.chords() > 0);
+        assert!(sequence.len() < board_metrics(&field).bbbv as usize);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json() {
+        let sequence = ClickSequence {
+            actions: vec![
+                ClickAction::Reveal { x: 0, y: 0 },
+                ClickAction::Flag { x: 2, y: 1 },
+                ClickAction::Chord { x: 1, y: 1 },
+            ],
+        };
+        let json: serde_json::Value = serde_json::from_str(&sequence.as_json()).unwrap();
+
+        assert_eq!(json["clicks"], 3);
+        assert_eq!(json["chords"], 1);
+        assert_eq!(json["actions"][1]["action"], "flag");
+        assert_eq!(json["actions"][1]["x"], 2);
+    }
+
+    #[test]
+    fn test_testing_fields() {
+        for path in [
+            "generated/testing/hard.minesweeper",
+            "generated/testing/evil_ng_field.minesweeper",
+        ] {
+            let field = DefinedField::from_file(path).expect("Failed to load testing file");
+            let solver = solved(&field);
+            let sequence = solver.get_click_sequence();
+            replay(&solver, &sequence);
+
+            let safe_cells = (field.get_width() * field.get_height() - field.get_mines()) as usize;
+            assert!(sequence.reveals() > 0);
+            assert!(sequence.len() < safe_cells);
+            assert_eq!(
+                sequence.len(),
+                sequence.reveals() + sequence.flags() + sequence.chords()
+            );
+        }
+    }
+
+    #[test]
+    fn test_stuck_solver() {
+        let field = DefinedField::from_file("generated/testing/extended_box_logic.minesweeper")
+            .expect("Failed to load testing file");
+        let mut solver = Solver::new(&field, &SolverConfig::default());
+        solver.solve().unwrap();
+
+        // Only the part the solver got through is played
+        let sequence = solver.get_click_sequence();
+        let mut board = PlayerBoard::new(&solver);
+        for &action in sequence.get_actions() {
+            board.apply(action);
+        }
+        for x in 0..solver.width {
+            for y in 0..solver.height {
+                let revealed = board.get(x, y) == PlayerCell::Revealed;
+                assert_eq!(
+                    revealed,
+                    matches!(solver.get_state(x, y), CellState::Revealed(_))
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use super::budget::SolverBudget;
+use super::strategy::{SolvingStrategy, Strategy};
+use std::fmt;
+use std::sync::Arc;
+
+/// Selects which solving strategies run and in which order.
+/// After every successful deduction the solver restarts from the first strategy,
+/// so cheaper strategies should come first.
+/// The budget limits how long a run may take, see [`SolverBudget`].
+#[derive(Clone)]
+pub struct SolverConfig {
+    strategies: Vec<Arc<dyn Strategy>>,
+    budget: SolverBudget,
+}
+
+impl SolverConfig {
+    pub fn new(strategies: Vec<SolvingStrategy>) -> Self {
+        SolverConfig {
+            strategies: strategies
+                .into_iter()
+                .map(|strategy| Arc::new(strategy) as Arc<dyn Strategy>)
+                .collect(),
+            budget: SolverBudget::default(),
+        }
+    }
+
+    /// Appends a strategy, built-in or user-defined, to the end of the pipeline.
+    pub fn with_strategy(mut self, strategy: impl Strategy + 'static) -> Self {
+        self.strategies.push(Arc::new(strategy));
+        self
+    }
+
+    pub fn with_budget(mut self, budget: SolverBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    pub fn get_strategies(&self) -> &[Arc<dyn Strategy>] {
+        &self.strategies
+    }
+
+    pub fn get_budget(&self) -> &SolverBudget {
+        &self.budget
+    }
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig::new(SolvingStrategy::iter().collect())
+    }
+}
+
+impl fmt::Debug for SolverConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.strategies.iter().map(|strategy| strategy.name()))
+            .finish()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, is_solvable};
+
+    #[test]
+    fn test_default_enables_all_strategies() {
+        let config = SolverConfig::default();
+
+        let names: Vec<&str> = config.get_strategies().iter().map(|s| s.name()).collect();
+
+        assert_eq!(
+            names,
+            [
+                "Simple",
+                "Reduction",
+                "Patterns",
+                "BoxLogic",
+                "LinearAlgebra",
+                "Sat",
+                "GlobalCount",
+                "MineCount"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_restricted_config() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        let simple_only = SolverConfig::new(vec![SolvingStrategy::Simple]);
+
+        assert!(
+            !is_solvable(&field, &simple_only),
+            "Hard field shouldn't be solvable with the simple strategy alone"
+        );
+        assert!(
+            is_solvable(&field, &SolverConfig::default()),
+            "Hard field should be solvable with all strategies"
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use super::{CellState, Rule, Solver};
+use crate::Cell;
+use std::collections::HashMap;
+
+/*
+Deduction dependencies:
+
+Every solving step after the start uses some revealed numbers and the flags around them, see the
+explanations of its finding. Each of those cells was revealed or flagged by an earlier step, which
+gives an edge from the step to that earlier one. Steps without explanations (custom strategies)
+depend on the numbers bordering the cells they decided.
+- Global count deductions depend on every flag placed before them, as they use the remaining mine count.
+- The SAT strategy only uses the remaining mine count as an upper bound, this is ignored.
+- Cells which were revealed before the first step (boards built with Solver::from_visible) have no step.
+
+The depth of a step is the length of its longest dependency chain. Steps of the same depth
+could have been made at the same time, so a field "funnels" where a depth has a single step
+that everything deeper depends on.
+*/
+
+/// A revealed number or flag a step used, and the earlier step which revealed or flagged it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Dependency {
+    pub step: usize,
+    pub cell: (u32, u32),
+}
+
+/// Which earlier steps each solving step depended on, see [`Solver::get_deduction_graph`].
+/// Steps are indexed like [`Solver::get_solving_steps`].
+#[derive(Debug, Clone)]
+pub struct DeductionGraph {
+    dependencies: Vec<Vec<Dependency>>,
+    depths: Vec<usize>,
+    /// The cells each step revealed or flagged
+    decided: Vec<Vec<(u32, u32)>>,
+}
+
+impl DeductionGraph {
+    pub fn len(&self) -> usize {
+        self.dependencies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dependencies.is_empty()
+    }
+
+    /// The numbers and flags a step used, with the steps that provided them
+    pub fn get_dependencies(&self, step: usize) -> &[Dependency] {
+        &self.dependencies[step]
+    }
+
+    /// Length of the longest dependency chain ending at a step, 0 for steps without dependencies
+    pub fn get_depth(&self, step: usize) -> usize {
+        self.depths[step]
+    }
+
+    /// Number of steps on the longest dependency chain
+    pub fn critical_path_length(&self) -> usize {
+        self.depths.iter().max().map_or(0, |depth| depth + 1)
+    }
+
+    /// The steps of one longest dependency chain, in solving order
+    pub fn critical_path(&self) -> Vec<usize> {
+        let Some(mut step) = (0..self.len()).max_by_key(|&step| (self.depths[step], step)) else {
+            return Vec::new();
+        };
+
+        let mut path = vec![step];
+        while self.depths[step] > 0 {
+            step = self.dependencies[step]
+                .iter()
+                .map(|dependency| dependency.step)
+                .find(|&previous| self.depths[previous] + 1 == self.depths[step])
+                .expect("a step deeper than 0 has a dependency one level above");
+            path.push(step);
+        }
+
+        path.reverse();
+        path
+    }
+
+    /// Most steps sharing a depth, i.e. how many deductions could be made at once
+    pub fn max_parallelism(&self) -> usize {
+        let mut widths = vec![0; self.critical_path_length()];
+        for &depth in &self.depths {
+            widths[depth] += 1;
+        }
+        widths.into_iter().max().unwrap_or(0)
+    }
+
+    /// Steps every deeper step depends on, directly or indirectly.
+    /// The start step and steps at the deepest level are left out.
+    pub fn bottlenecks(&self) -> Vec<usize> {
+        let ancestors = self.ancestors();
+        let max_depth = self.critical_path_length().saturating_sub(1);
+
+        (1..self.len())
+            .filter(|&step| self.depths[step] < max_depth)
+            .filter(|&step| {
+                (0..self.len())
+                    .filter(|&later| self.depths[later] > self.depths[step])
+                    .all(|later| ancestors[later][step / 64] & (1 << (step % 64)) != 0)
+            })
+            .collect()
+    }
+
+    /// The cells of bottleneck steps which later steps used
+    pub fn bottleneck_cells(&self) -> Vec<(u32, u32)> {
+        let bottlenecks = self.bottlenecks();
+
+        let mut cells: Vec<(u32, u32)> = self
+            .dependencies
+            .iter()
+            .flatten()
+            .filter(|dependency| bottlenecks.contains(&dependency.step))
+            .map(|dependency| dependency.cell)
+            .collect();
+        cells.sort_by_key(|&(x, y)| (y, x));
+        cells.dedup();
+        cells
+    }
+
+    /// The cells a step revealed or flagged
+    pub fn get_decided_cells(&self, step: usize) -> &[(u32, u32)] {
+        &self.decided[step]
+    }
+
+    /// Bitsets of all direct and indirect dependencies of every step
+    fn ancestors(&self) -> Vec<Vec<u64>> {
+        let words = self.len().div_ceil(64);
+        let mut ancestors: Vec<Vec<u64>> = Vec::with_capacity(self.len());
+
+        // Dependencies always point to earlier steps
+        for dependencies in &self.dependencies {
+            let mut bits = vec![0u64; words];
+            for dependency in dependencies {
+                bits[dependency.step / 64] |= 1 << (dependency.step % 64);
+                for (word, inherited) in bits.iter_mut().zip(&ancestors[dependency.step]) {
+                    *word |= inherited;
+                }
+            }
+            ancestors.push(bits);
+        }
+
+        ancestors
+    }
+}
+
+impl Solver {
+    /// Builds the dependency graph of the solving steps made so far
+    pub fn get_deduction_graph(&self) -> DeductionGraph {
+        let steps = &self.solving_steps;
+
+        let mut decided_by: HashMap<(u32, u32), usize> = HashMap::new();
+        let mut flags_by_step: Vec<Vec<(u32, u32)>> = Vec::with_capacity(steps.len());
+        let mut decided: Vec<Vec<(u32, u32)>> = Vec::with_capacity(steps.len());
+        for (step, finding) in steps.iter().enumerate() {
+            let revealed = finding
+                .get_safe_fields()
+                .iter()
+                .chain(finding.get_recursive_revelations().iter().flatten());
+            let cells: Vec<(u32, u32)> =
+                revealed.chain(finding.get_mine_fields()).copied().collect();
+            for &cell in &cells {
+                decided_by.entry(cell).or_insert(step);
+            }
+            flags_by_step.push(finding.get_mine_fields().clone());
+            decided.push(cells);
+        }
+
+        let mut dependencies = Vec::with_capacity(steps.len());
+        let mut depths: Vec<usize> = Vec::with_capacity(steps.len());
+        for (step, finding) in steps.iter().enumerate() {
+            let mut numbers: Vec<(u32, u32)> = Vec::new();
+            let mut uses_mine_count = false;
+
+            for explanation in finding.get_explanations() {
+                numbers.extend(explanation.constraints.iter().map(|c| c.position));
+                uses_mine_count |= matches!(explanation.rule, Rule::GlobalCount { .. });
+            }
+            if finding.get_explanations().is_empty() && step > 0 {
+                for &(x, y) in finding
+                    .get_safe_fields()
+                    .iter()
+                    .chain(finding.get_mine_fields())
+                {
+                    numbers.extend(self.surrounding_fields(x, y, None).filter(|&(nx, ny)| {
+                        matches!(self.get_state(nx, ny), CellState::Revealed(Cell::Number(_)))
+                    }));
+                }
+            }
+
+            let earlier = |cell: &(u32, u32)| decided_by.get(cell).filter(|&&by| by < step);
+            let mut used: Vec<Dependency> = Vec::new();
+            for &(x, y) in &numbers {
+                if let Some(&by) = earlier(&(x, y)) {
+                    used.push(Dependency {
+                        step: by,
+                        cell: (x, y),
+                    });
+                }
+                // Flags around the number made up its reduced count
+                for neighbour in self.surrounding_fields(x, y, None) {
+                    if let Some(&by) = earlier(&neighbour)
+                        && flags_by_step[by].contains(&neighbour)
+                    {
+                        used.push(Dependency {
+                            step: by,
+                            cell: neighbour,
+                        });
+                    }
+                }
+            }
+            if uses_mine_count {
+                for (by, flags) in flags_by_step.iter().enumerate().take(step) {
+                    used.extend(flags.iter().map(|&cell| Dependency { step: by, cell }));
+                }
+            }
+            used.sort();
+            used.dedup();
+
+            let depth = used
+                .iter()
+                .map(|dependency| depths[dependency.step] + 1)
+                .max()
+                .unwrap_or(0);
+            depths.push(depth);
+            dependencies.push(used);
+        }
+
+        DeductionGraph {
+            dependencies,
+            depths,
+            decided,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, create_solver};
+
+    fn solved_graph(path: &str) -> DeductionGraph {
+        let field = DefinedField::from_file(path).expect("Failed to load testing file");
+        let mut solver = create_solver(&field);
+        solver.solve().unwrap();
+        assert!(solver.is_solved());
+        solver.get_deduction_graph()
+    }
+
+    #[test]
+    fn test_dependencies_point_backwards() {
+        for path in [
+            "generated/testing/hard.minesweeper",
+            "generated/testing/evil_ng_field.minesweeper",
+        ] {
+            let graph = solved_graph(path);
+            assert!(graph.len() > 1);
+            assert_eq!(graph.get_depth(0), 0);
+
+            for step in 1..graph.len() {
+                let dependencies = graph.get_dependencies(step);
+                assert!(!dependencies.is_empty(), "step {} depends on nothing", step);
+                for dependency in dependencies {
+                    assert!(dependency.step < step);
+                    assert!(
+                        graph
+                            .get_decided_cells(dependency.step)
+                            .contains(&dependency.cell)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let graph = solved_graph("generated/testing/hard.minesweeper");
+
+        let path = graph.critical_path();
+        assert_eq!(path.len(), graph.critical_path_length());
+        assert_eq!(path[0], 0);
+        for pair in path.windows(2) {
+            assert!(
+                graph
+                    .get_dependencies(pair[1])
+                    .iter()
+                    .any(|d| d.step == pair[0])
+            );
+            assert_eq!(graph.get_depth(pair[1]), graph.get_depth(pair[0]) + 1);
+        }
+
+        // Levels of parallel steps cover all steps
+        assert!(graph.max_parallelism() >= 1);
+        assert!(graph.max_parallelism() * graph.critical_path_length() >= graph.len());
+    }
+
+    #[test]
+    fn test_bottlenecks() {
+        // Each step of the 1-2-1 field needs a cell the step before decided,
+        // so all steps but the start and the last one are bottlenecks
+        let graph = solved_graph("generated/patterns/1-2-1-R.minesweeper");
+        assert_eq!(graph.len(), 5);
+        assert_eq!(graph.bottlenecks(), vec![1, 2, 3]);
+        // (2,4) is revealed in step 3 but no later step uses it
+        assert_eq!(
+            graph.bottleneck_cells(),
+            vec![(2, 2), (2, 3), (1, 4), (3, 4)]
+        );
+
+        // Flagging the three mines next to the 3 opens the rest of the field at once,
+        // the 1 revealed at (1,4) then gives the last mine in the corner
+        let graph = solved_graph("generated/patterns/b1.minesweeper");
+        assert_eq!(graph.bottlenecks(), vec![1, 2]);
+        assert_eq!(
+            graph.bottleneck_cells(),
+            vec![(3, 1), (3, 2), (3, 3), (1, 4)]
+        );
+    }
+}
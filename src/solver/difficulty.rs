@@ -0,0 +1,258 @@
+use super::{Solver, SolverConfig, SolverError};
+use crate::MineSweeperField;
+
+/*
+Difficulty rating:
+
+The field is solved step by step, recording for every step
+- which strategy made it, weighted by how hard the technique is for a player, see Strategy::difficulty_weight
+  (Simple / MineCount 1, Reduction 3, Patterns 4, BoxLogic 5, LinearAlgebra 6, Sat 8, GlobalCount 12, custom strategies 5 by default)
+- for SAT steps, the size of every component and the nodes its search explored, as the step recorded them
+The deduction graph of the finished run adds
+- the critical path: the longest chain of steps which depend on each other
+- single deduction steps: depths of the graph with exactly one step holding one deduction,
+  where the player has to find the one move the field allows
+
+  raw = technique weights + 2 * sum(log2(1 + search nodes)) + critical path + 3 * single deduction steps
+
+The raw value is mapped to 0..100 with
+  score = 100 * (1 - exp(-raw / 150))
+It is not scaled by the field size, a bigger field takes more deductions and is harder to finish.
+Fields which can't be solved without guessing are rated for the part the solver got through.
+*/
+
+const SCALE: f64 = 150.0;
+
+/// Coarse difficulty class of a field, derived from [`Difficulty::score`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DifficultyTier {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    Evil,
+}
+
+impl DifficultyTier {
+    pub fn from_score(score: f64) -> Self {
+        match score {
+            s if s < 20.0 => DifficultyTier::Easy,
+            s if s < 40.0 => DifficultyTier::Medium,
+            s if s < 60.0 => DifficultyTier::Hard,
+            s if s < 80.0 => DifficultyTier::Expert,
+            _ => DifficultyTier::Evil,
+        }
+    }
+}
+
+impl std::fmt::Display for DifficultyTier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Difficulty analysis of a field, see [`difficulty`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difficulty {
+    /// 0 (trivial) to 100 (hardest)
+    pub score: f64,
+    pub tier: DifficultyTier,
+    /// False if the solver got stuck and the rating only covers the solved part
+    pub solved: bool,
+    /// Solving steps including the start
+    pub steps: usize,
+    /// Steps made by each strategy, in the order of the solver config
+    pub strategy_counts: Vec<(String, usize)>,
+    /// Components searched in SAT steps
+    pub sat_components: usize,
+    pub largest_sat_component: usize,
+    /// Search nodes explored by all SAT steps
+    pub sat_search_nodes: usize,
+    pub critical_path_length: usize,
+    pub single_deduction_steps: usize,
+}
+
+/// Solves `field` and rates how hard it is for a player without guessing.
+pub fn difficulty(
+    field: &impl MineSweeperField,
+    config: &SolverConfig,
+) -> Result<Difficulty, SolverError> {
+    let mut solver = Solver::new(field, config);
+
+    let mut search = 0.0;
+    let mut sat_components = 0;
+    let mut largest_sat_component = 0;
+    let mut sat_search_nodes = 0;
+
+    while let Some(finding) = solver.step() {
+        for &(size, nodes) in finding.get_searched_components() {
+            sat_components += 1;
+            largest_sat_component = largest_sat_component.max(size);
+            sat_search_nodes += nodes;
+            search += (1.0 + nodes as f64).log2();
+        }
+    }
+    if let Some(err) = solver.get_error() {
+        return Err(err.clone());
+    }
+
+    // Every step after the start is the one success of its strategy, counted by position in
+    // the config so strategies sharing a name are told apart
+    let strategy_counts: Vec<(String, usize)> = solver
+        .get_stats()
+        .strategies
+        .into_iter()
+        .map(|stats| (stats.name, stats.successes))
+        .collect();
+    let technique: f64 = config
+        .get_strategies()
+        .iter()
+        .zip(&strategy_counts)
+        .map(|(strategy, (_, count))| strategy.difficulty_weight() * *count as f64)
+        .sum();
+
+    let graph = solver.get_deduction_graph();
+    let critical_path_length = graph.critical_path_length();
+
+    let mut widths = vec![Vec::new(); critical_path_length];
+    for step in 0..graph.len() {
+        widths[graph.get_depth(step)].push(step);
+    }
+    let single_deduction_steps = widths
+        .iter()
+        .skip(1)
+        .filter(|steps| {
+            steps.len() == 1 && solver.solving_steps[steps[0]].get_explanations().len() <= 1
+        })
+        .count();
+
+    let raw = technique
+        + 2.0 * search
+        + critical_path_length as f64
+        + 3.0 * single_deduction_steps as f64;
+    let score = 100.0 * (1.0 - (-raw / SCALE).exp());
+
+    Ok(Difficulty {
+        score,
+        tier: DifficultyTier::from_score(score),
+        solved: solver.is_solved(),
+        steps: graph.len(),
+        strategy_counts,
+        sat_components,
+        largest_sat_component,
+        sat_search_nodes,
+        critical_path_length,
+        single_deduction_steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, Finding, SolverView, SolvingStrategy, Strategy};
+
+    fn rate(path: &str) -> Difficulty {
+        let field = DefinedField::from_file(path).expect("Failed to load testing file");
+        difficulty(&field, &SolverConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_harder_fields_score_higher() {
+        let pattern = rate("generated/patterns/1-1.minesweeper");
+        let hard = rate("generated/testing/hard.minesweeper");
+        let evil = rate("generated/testing/evil_ng_field.minesweeper");
+
+        assert!(hard.solved && evil.solved);
+        assert!(pattern.score < hard.score);
+        assert!(hard.score < evil.score);
+        assert!(pattern.tier <= hard.tier && hard.tier <= evil.tier);
+        assert!((0.0..=100.0).contains(&evil.score));
+    }
+
+    #[test]
+    fn test_trace_counts() {
+        let hard = rate("generated/testing/hard.minesweeper");
+
+        let counted: usize = hard.strategy_counts.iter().map(|(_, count)| count).sum();
+        assert_eq!(counted + 1, hard.steps);
+        assert!(hard.critical_path_length <= hard.steps);
+        assert!(hard.single_deduction_steps < hard.critical_path_length);
+
+        // The hard field needs the SAT strategy
+        let sat_steps = hard.strategy_counts.iter().find(|(name, _)| name == "Sat");
+        assert!(sat_steps.is_some_and(|&(_, count)| count > 0));
+        assert!(hard.sat_components > 0);
+        assert!(hard.largest_sat_component > 0);
+        assert!(hard.sat_search_nodes >= hard.sat_components);
+    }
+
+    /// The simple strategy, declared as much harder
+    struct HeavySimple;
+
+    impl Strategy for HeavySimple {
+        fn name(&self) -> &str {
+            "HeavySimple"
+        }
+
+        fn solve(&self, view: &SolverView) -> Finding {
+            SolvingStrategy::Simple.solve(view)
+        }
+
+        fn difficulty_weight(&self) -> f64 {
+            20.0
+        }
+    }
+
+    #[test]
+    fn test_strategies_declare_their_weight() {
+        let field = DefinedField::from_file("generated/patterns/b1.minesweeper")
+            .expect("Failed to load pattern file");
+
+        let simple = SolverConfig::new(vec![SolvingStrategy::Simple]);
+        let heavy = SolverConfig::new(vec![]).with_strategy(HeavySimple);
+        let simple = difficulty(&field, &simple).unwrap();
+        let heavy = difficulty(&field, &heavy).unwrap();
+
+        assert!(simple.solved && heavy.solved);
+        assert_eq!(simple.steps, heavy.steps);
+        assert!(simple.score < heavy.score);
+    }
+
+    /// The reduction strategy under the name of another one
+    struct RenamedReduction;
+
+    impl Strategy for RenamedReduction {
+        fn name(&self) -> &str {
+            "Simple"
+        }
+
+        fn solve(&self, view: &SolverView) -> Finding {
+            SolvingStrategy::Reduction.solve(view)
+        }
+    }
+
+    #[test]
+    fn test_strategies_sharing_a_name() {
+        let field = DefinedField::from_file("generated/patterns/1-2-2-1.minesweeper")
+            .expect("Failed to load pattern file");
+
+        // One Reduction step, then one Simple step
+        let config =
+            SolverConfig::new(vec![SolvingStrategy::Simple]).with_strategy(RenamedReduction);
+        let rating = difficulty(&field, &config).unwrap();
+
+        assert!(rating.solved);
+        assert_eq!(
+            rating.strategy_counts,
+            vec![("Simple".to_string(), 1), ("Simple".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_tiers() {
+        assert_eq!(DifficultyTier::from_score(0.0), DifficultyTier::Easy);
+        assert_eq!(DifficultyTier::from_score(39.9), DifficultyTier::Medium);
+        assert_eq!(DifficultyTier::from_score(60.0), DifficultyTier::Expert);
+        assert_eq!(DifficultyTier::from_score(100.0), DifficultyTier::Evil);
+    }
+}
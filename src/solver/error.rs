@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// Reasons why the solver had to stop before it could finish solving.
+/// The board keeps all changes made before the error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolverError {
+    /// The start cell is outside of the field or not empty
+    InvalidStart { x: u32, y: u32, reason: String },
+    /// A cell was revealed which is a mine, or a safe cell was flagged
+    Contradiction { x: u32, y: u32 },
+    /// A revealed number borders more flags than it shows
+    InconsistentFlags {
+        x: u32,
+        y: u32,
+        number: u8,
+        flags: u8,
+    },
+    /// A cell had to be revealed whose content the solver doesn't know
+    UnknownCell { x: u32, y: u32 },
+    /// The visible board handed to the solver is malformed
+    InvalidBoard(String),
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::InvalidStart { x, y, reason } => {
+                write!(f, "Invalid start cell ({}, {}): {}", x, y, reason)
+            }
+            SolverError::Contradiction { x, y } => {
+                write!(
+                    f,
+                    "Contradiction at ({}, {}): deduction doesn't match the field",
+                    x, y
+                )
+            }
+            SolverError::InconsistentFlags {
+                x,
+                y,
+                number,
+                flags,
+            } => {
+                write!(
+                    f,
+                    "Inconsistent flags at ({}, {}): {} flags around a {}",
+                    x, y, flags, number
+                )
+            }
+            SolverError::UnknownCell { x, y } => {
+                write!(f, "Can't reveal ({}, {}): its content is unknown", x, y)
+            }
+            SolverError::InvalidBoard(reason) => write!(f, "Invalid board: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
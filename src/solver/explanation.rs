@@ -0,0 +1,356 @@
+use super::{CellState, Solver};
+use crate::Cell;
+use std::fmt;
+
+/// The rule a deduction is based on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// A single number is either satisfied by its flags or needs all its hidden neighbours
+    SingleCell,
+    /// The hidden neighbours of one number are a subset of another one's
+    SubsetReduction,
+    /// A known pattern, by name
+    Pattern(&'static str),
+    /// All mine arrangements satisfying the numbers of a component agree on these cells
+    CaseSplit,
+    /// Bounds on the mines of overlapping regions, derived from a set of numbers
+    BoxLogic,
+    /// A combination of numbers, added and subtracted, only fits one way
+    LinearCombination,
+    /// The number of remaining mines decides these cells
+    GlobalCount { remaining_mines: u32 },
+}
+
+/// A revealed number used by a deduction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintCell {
+    pub position: (u32, u32),
+    pub number: u8,
+    /// The number minus its bordering flags
+    pub reduced_count: u8,
+}
+
+/// Why a set of cells is safe or a mine, see [`crate::Finding::get_explanations`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub rule: Rule,
+    /// The numbers involved, for subset reductions the larger area comes first
+    pub constraints: Vec<ConstraintCell>,
+    pub mine_fields: Vec<(u32, u32)>,
+    pub safe_fields: Vec<(u32, u32)>,
+}
+
+impl Explanation {
+    pub fn new(
+        rule: Rule,
+        constraints: Vec<ConstraintCell>,
+        mine_fields: Vec<(u32, u32)>,
+        safe_fields: Vec<(u32, u32)>,
+    ) -> Self {
+        Explanation {
+            rule,
+            constraints,
+            mine_fields,
+            safe_fields,
+        }
+    }
+
+    /// Renders the explanation as a sentence, e.g.
+    /// "the 2 at (4,5) minus the 1 at (5,5) leaves one mine in {(6,4)}"
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let numbers = self
+            .constraints
+            .iter()
+            .map(describe_number)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match &self.rule {
+            Rule::SingleCell => {
+                let number = self.constraints.first().map(describe_number);
+                let number = number.unwrap_or_default();
+                if self.mine_fields.is_empty() {
+                    write!(
+                        f,
+                        "{} has all its mines flagged, so {} {} safe",
+                        number,
+                        cells(&self.safe_fields),
+                        verb(&self.safe_fields)
+                    )
+                } else {
+                    write!(
+                        f,
+                        "{} needs all its hidden neighbours, so {} {} {}",
+                        number,
+                        cells(&self.mine_fields),
+                        verb(&self.mine_fields),
+                        mines_word(self.mine_fields.len())
+                    )
+                }
+            }
+            Rule::SubsetReduction => {
+                let described: Vec<String> = self.constraints.iter().map(describe_number).collect();
+                write!(f, "{}", described.join(" minus "))?;
+                if self.mine_fields.is_empty() {
+                    write!(f, " leaves no mine in {}", cells(&self.safe_fields))
+                } else {
+                    write!(
+                        f,
+                        " leaves {} in {}",
+                        mine_count(self.mine_fields.len()),
+                        cells(&self.mine_fields)
+                    )
+                }
+            }
+            Rule::Pattern(name) => {
+                write!(f, "the {} pattern of {}", name, numbers)?;
+                write_results(f, ":", &self.mine_fields, &self.safe_fields)
+            }
+            Rule::CaseSplit => {
+                write!(f, "every mine arrangement which fits {}", numbers)?;
+                write_results(f, " agrees:", &self.mine_fields, &self.safe_fields)
+            }
+            Rule::BoxLogic => {
+                write!(f, "the regions of {} bound the mines", numbers)?;
+                write_results(f, ":", &self.mine_fields, &self.safe_fields)
+            }
+            Rule::LinearCombination => {
+                write!(f, "combining {}", numbers)?;
+                write_results(f, " leaves one way:", &self.mine_fields, &self.safe_fields)
+            }
+            Rule::GlobalCount { remaining_mines } => {
+                write!(f, "with {} left", mine_count(*remaining_mines as usize))?;
+                if !numbers.is_empty() {
+                    write!(f, " and {}", numbers)?;
+                }
+                write_results(f, ",", &self.mine_fields, &self.safe_fields)
+            }
+        }
+    }
+}
+
+fn write_results(
+    f: &mut fmt::Formatter<'_>,
+    separator: &str,
+    mine_fields: &[(u32, u32)],
+    safe_fields: &[(u32, u32)],
+) -> fmt::Result {
+    write!(f, "{}", separator)?;
+    if !mine_fields.is_empty() {
+        write!(
+            f,
+            " {} {} {}",
+            cells(mine_fields),
+            verb(mine_fields),
+            mines_word(mine_fields.len())
+        )?;
+    }
+    if !mine_fields.is_empty() && !safe_fields.is_empty() {
+        write!(f, " and")?;
+    }
+    if !safe_fields.is_empty() {
+        write!(f, " {} {} safe", cells(safe_fields), verb(safe_fields))?;
+    }
+    Ok(())
+}
+
+fn describe_number(cell: &ConstraintCell) -> String {
+    let (x, y) = cell.position;
+    let flags = cell.number.saturating_sub(cell.reduced_count);
+    match flags {
+        0 => format!("the {} at ({},{})", cell.number, x, y),
+        1 => format!("the {} at ({},{}) with 1 flag", cell.number, x, y),
+        _ => format!("the {} at ({},{}) with {} flags", cell.number, x, y, flags),
+    }
+}
+
+fn cells(fields: &[(u32, u32)]) -> String {
+    let positions: Vec<String> = fields
+        .iter()
+        .map(|(x, y)| format!("({},{})", x, y))
+        .collect();
+    format!("{{{}}}", positions.join(", "))
+}
+
+fn verb(fields: &[(u32, u32)]) -> &'static str {
+    if fields.len() == 1 { "is" } else { "are" }
+}
+
+fn mines_word(count: usize) -> &'static str {
+    if count == 1 { "a mine" } else { "mines" }
+}
+
+fn mine_count(count: usize) -> String {
+    match count {
+        0 => "no mine".into(),
+        1 => "one mine".into(),
+        n => format!("{} mines", n),
+    }
+}
+
+impl Solver {
+    /// The revealed number at a position as used by explanations
+    pub(super) fn constraint_cell(&self, x: u32, y: u32) -> ConstraintCell {
+        let number = match self.get_state(x, y) {
+            CellState::Revealed(Cell::Number(n)) => *n,
+            _ => 0,
+        };
+
+        ConstraintCell {
+            position: (x, y),
+            number,
+            reduced_count: number.saturating_sub(self.get_surrounding_flag_count(x, y)),
+        }
+    }
+
+    /// All frontier numbers bordering one of the given hidden cells
+    pub(super) fn constraint_cells_of(&self, fields: &[(u32, u32)]) -> Vec<ConstraintCell> {
+        self.frontier()
+            .filter(|&(x, y)| {
+                self.get_surrounding_unrevealed(x, y)
+                    .iter()
+                    .any(|field| fields.contains(field))
+            })
+            .map(|(x, y)| self.constraint_cell(x, y))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, create_solver};
+    use std::collections::HashSet;
+
+    fn number(position: (u32, u32), number: u8, reduced_count: u8) -> ConstraintCell {
+        ConstraintCell {
+            position,
+            number,
+            reduced_count,
+        }
+    }
+
+    #[test]
+    fn test_render_subset_reduction() {
+        let explanation = Explanation::new(
+            Rule::SubsetReduction,
+            vec![number((4, 5), 2, 2), number((5, 5), 1, 1)],
+            vec![(6, 4)],
+            vec![],
+        );
+
+        assert_eq!(
+            explanation.render(),
+            "the 2 at (4,5) minus the 1 at (5,5) leaves one mine in {(6,4)}"
+        );
+    }
+
+    #[test]
+    fn test_render_inconsistent_number() {
+        // Built by hand with more mines left than the number shows
+        let explanation = Explanation::new(
+            Rule::SingleCell,
+            vec![number((0, 0), 1, 2)],
+            vec![(1, 0)],
+            vec![],
+        );
+
+        assert!(explanation.render().starts_with("the 1 at (0,0) "));
+    }
+
+    #[test]
+    fn test_render_rules() {
+        let single = Explanation::new(
+            Rule::SingleCell,
+            vec![number((1, 1), 3, 1)],
+            vec![],
+            vec![(0, 0), (2, 2)],
+        );
+        assert_eq!(
+            single.render(),
+            "the 3 at (1,1) with 2 flags has all its mines flagged, so {(0,0), (2,2)} are safe"
+        );
+
+        let pattern = Explanation::new(
+            Rule::Pattern("1-2-1"),
+            vec![
+                number((0, 1), 1, 1),
+                number((1, 1), 2, 2),
+                number((2, 1), 1, 1),
+            ],
+            vec![(0, 2), (2, 2)],
+            vec![(1, 2)],
+        );
+        assert_eq!(
+            pattern.render(),
+            "the 1-2-1 pattern of the 1 at (0,1), the 2 at (1,1), the 1 at (2,1): \
+             {(0,2), (2,2)} are mines and {(1,2)} is safe"
+        );
+
+        let global = Explanation::new(
+            Rule::GlobalCount { remaining_mines: 0 },
+            vec![],
+            vec![],
+            vec![(3, 3)],
+        );
+        assert_eq!(global.render(), "with no mine left, {(3,3)} is safe");
+    }
+
+    #[test]
+    fn test_findings_are_explained() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+        let mut solver = create_solver(&field);
+
+        let findings: Vec<_> = solver.steps().collect();
+        assert!(solver.is_solved());
+
+        for finding in findings.iter().filter(|f| f.get_strategy().is_some()) {
+            let explanations = finding.get_explanations();
+            assert!(!explanations.is_empty(), "{:?} has no explanation", finding);
+
+            // Together the explanations cover exactly the fields of the finding
+            let explained: HashSet<_> = explanations
+                .iter()
+                .flat_map(|e| e.mine_fields.iter().chain(&e.safe_fields))
+                .collect();
+            let found: HashSet<_> = finding
+                .get_mine_fields()
+                .iter()
+                .chain(finding.get_safe_fields())
+                .collect();
+            assert_eq!(explained, found);
+
+            assert!(
+                explanations.iter().all(
+                    |e| !e.constraints.is_empty() || matches!(e.rule, Rule::GlobalCount { .. })
+                )
+            );
+            assert_eq!(finding.explain().lines().count(), explanations.len());
+        }
+    }
+
+    #[test]
+    fn test_reduction_is_explained() {
+        let field = DefinedField::from_file("generated/patterns/1-1.minesweeper")
+            .expect("Failed to load pattern file");
+        let mut solver = create_solver(&field);
+
+        let finding = solver
+            .steps()
+            .find(|f| f.get_strategy() == Some("Reduction"))
+            .expect("The 1-1 pattern needs a reduction");
+
+        for explanation in finding.get_explanations() {
+            assert_eq!(explanation.rule, Rule::SubsetReduction);
+            assert_eq!(explanation.constraints.len(), 2);
+            assert!(explanation.render().contains(" minus "));
+        }
+    }
+}
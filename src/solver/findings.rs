@@ -1,8 +1,17 @@
+use super::budget::GiveUpReason;
+use super::explanation::Explanation;
+use std::collections::HashSet;
+
 #[derive(Debug, Clone)]
 pub struct Finding {
     safe_fields: Vec<(u32, u32)>,
     recursive_informations: Vec<Vec<(u32, u32)>>, // Fields which were also revealed recursively after applying this Findings informations
     mine_fields: Vec<(u32, u32)>,
+    patterns: Vec<&'static str>, // Names of the known patterns which led to this Finding
+    strategy: Option<String>, // Name of the strategy which made this Finding, None for the start cell
+    explanations: Vec<Explanation>, // Why the fields are safe or mines, one entry per deduction
+    gave_up: Option<GiveUpReason>, // Set if the strategy ran out of budget before finishing its search
+    searched_components: Vec<(usize, usize)>, // Size and explored search nodes of every searched component
 }
 
 impl Finding {
@@ -11,6 +20,11 @@ impl Finding {
             safe_fields: Vec::new(),
             recursive_informations: Vec::new(),
             mine_fields: Vec::new(),
+            patterns: Vec::new(),
+            strategy: None,
+            explanations: Vec::new(),
+            gave_up: None,
+            searched_components: Vec::new(),
         }
     }
 
@@ -42,23 +56,56 @@ impl Finding {
         }
     }
 
+    pub fn add_pattern(&mut self, name: &'static str) {
+        if !self.patterns.contains(&name) {
+            self.patterns.push(name);
+        }
+    }
+
+    pub fn add_explanation(&mut self, explanation: Explanation) {
+        if !self.explanations.contains(&explanation) {
+            self.explanations.push(explanation);
+        }
+    }
+
+    /// Marks that the strategy stopped early, it may have missed deductions
+    pub fn give_up(&mut self, reason: GiveUpReason) {
+        self.gave_up.get_or_insert(reason);
+    }
+
+    pub fn get_give_up_reason(&self) -> Option<GiveUpReason> {
+        self.gave_up
+    }
+
+    pub(crate) fn add_searched_component(&mut self, size: usize, search_nodes: usize) {
+        self.searched_components.push((size, search_nodes));
+    }
+
+    /// Size and explored search nodes of every component the strategy searched, empty for
+    /// strategies without a search
+    pub fn get_searched_components(&self) -> &Vec<(usize, usize)> {
+        &self.searched_components
+    }
+
+    pub(crate) fn set_strategy(&mut self, name: &str) {
+        self.strategy = Some(name.to_string());
+    }
+
     pub fn add_recursive_informations(&mut self, fields: Vec<Vec<(u32, u32)>>) {
-        for i in 0..fields.len() {
+        // Avoid adding fields which are already known as safe or were revealed in previous steps
+        let mut known: HashSet<(u32, u32)> =
+            HashSet::with_capacity(fields.iter().map(Vec::len).sum());
+        known.extend(&self.safe_fields);
+        known.extend(self.recursive_informations.iter().flatten());
+
+        for (i, layer) in fields.into_iter().enumerate() {
             if self.recursive_informations.len() <= i {
                 self.recursive_informations.push(Vec::new());
             }
-
-            // Avoid adding fields which are already known as safe or were revealed in previous steps
-            'field_loop: for field in fields[i].iter() {
-                if self.safe_fields.contains(field) {
-                    continue;
-                }
-                for j in 0..=i {
-                    if self.recursive_informations[j].contains(field) {
-                        continue 'field_loop;
-                    }
+            for field in layer {
+                if known.insert(field) {
+                    self.recursive_informations[i].push(field);
                 }
-                self.recursive_informations[i].push(*field);
             }
         }
     }
@@ -74,4 +121,33 @@ impl Finding {
     pub fn get_recursive_revelations(&self) -> &Vec<Vec<(u32, u32)>> {
         &self.recursive_informations
     }
+
+    pub fn get_patterns(&self) -> &Vec<&'static str> {
+        &self.patterns
+    }
+
+    pub fn get_strategy(&self) -> Option<&str> {
+        self.strategy.as_deref()
+    }
+
+    pub fn get_explanations(&self) -> &Vec<Explanation> {
+        &self.explanations
+    }
+
+    /// Renders all explanations as text, one deduction per line.
+    /// Findings of strategies without explanations only list their result.
+    pub fn explain(&self) -> String {
+        if !self.explanations.is_empty() {
+            let lines: Vec<String> = self.explanations.iter().map(|e| e.render()).collect();
+            return lines.join("\n");
+        }
+
+        match &self.strategy {
+            Some(name) => format!(
+                "{} found mines {:?} and safe cells {:?}",
+                name, self.mine_fields, self.safe_fields
+            ),
+            None => format!("opened {:?}", self.safe_fields),
+        }
+    }
 }
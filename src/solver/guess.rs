@@ -0,0 +1,230 @@
+use super::view::SolverView;
+use super::{CellState, Solver};
+use crate::Cell;
+
+/*
+Guess advisor:
+
+When no strategy can make progress, every hidden cell is rated by
+- its survival probability (exact, see get_mine_probabilities)
+- the chance that revealing it opens an empty area
+- the chance that the revealed number lets the configured strategies continue
+
+The number a cell will show is estimated from the mine probabilities of its hidden neighbours,
+treating them as independent. For the safest cells these probabilities are recomputed under the
+assumption that the cell itself is safe, and each possible number is written into a hypothetical
+copy of the board to ask the strategies whether they find anything with it.
+Only the safest cells are checked this way, since every check runs the strategies up to 9 times.
+These are ranked by their score, the remaining cells follow in order of survival probability:
+their progress is only a rough estimate and can't be compared with the checked ones.
+*/
+
+const PROGRESS_CANDIDATES: usize = 10;
+const MIN_VALUE_PROBABILITY: f64 = 1e-6;
+
+/// A rated guess, see [`Solver::suggest_guess`].
+#[derive(Debug, Clone)]
+pub struct GuessSuggestion {
+    pub position: (u32, u32),
+    /// Probability that the cell is not a mine
+    pub survival_probability: f64,
+    /// Probability that the cell is safe and empty, opening an area
+    pub opening_probability: f64,
+    /// Probability that the cell is safe and its number allows further deductions (includes openings)
+    pub progress_probability: f64,
+    /// `survival_probability * (1 + progress_probability)`, higher is better
+    pub score: f64,
+}
+
+impl Solver {
+    /// Suggests the best cell to guess when the solver is stuck.
+    /// Cells are ranked by survival probability weighted with the chance of making progress afterwards.
+    /// Returns `None` when there are no hidden cells left.
+    pub fn suggest_guess(&self) -> Option<GuessSuggestion> {
+        self.rate_guesses().into_iter().next()
+    }
+
+    /// All hidden cells rated as guesses, best first.
+    /// The safest cells are ranked by score, all others follow by survival probability
+    /// with `progress_probability` only estimated as their `opening_probability`.
+    pub fn rate_guesses(&self) -> Vec<GuessSuggestion> {
+        let model = self.probability_model();
+        let probabilities = model.probabilities();
+
+        let mut suggestions: Vec<GuessSuggestion> = self
+            .sorted_fields()
+            .filter_map(|(x, y)| {
+                let survival_probability = 1.0 - probabilities[x as usize][y as usize]?;
+                let opening_probability =
+                    survival_probability * self.empty_chance(x, y, &probabilities);
+
+                Some(GuessSuggestion {
+                    position: (x, y),
+                    survival_probability,
+                    opening_probability,
+                    progress_probability: opening_probability,
+                    score: survival_probability * (1.0 + opening_probability),
+                })
+            })
+            .collect();
+
+        sort_by_score(&mut suggestions, |s| s.survival_probability);
+
+        // Revisit the safest cells with the neighbour probabilities conditioned on the cell being safe
+        for suggestion in suggestions.iter_mut().take(PROGRESS_CANDIDATES) {
+            if suggestion.survival_probability <= 0.0 {
+                continue;
+            }
+
+            let (x, y) = suggestion.position;
+            let conditional = model.probabilities_assuming_safe((x, y));
+
+            suggestion.opening_probability =
+                suggestion.survival_probability * self.empty_chance(x, y, &conditional);
+            suggestion.progress_probability =
+                suggestion.survival_probability * self.progress_chance(x, y, &conditional);
+            suggestion.score =
+                suggestion.survival_probability * (1.0 + suggestion.progress_probability);
+        }
+
+        let checked = suggestions.len().min(PROGRESS_CANDIDATES);
+        sort_by_score(&mut suggestions[..checked], |s| s.score);
+        suggestions
+    }
+
+    /// Chance that none of the neighbours is a mine
+    fn empty_chance(&self, x: u32, y: u32, probabilities: &[Vec<Option<f64>>]) -> f64 {
+        if self.get_surrounding_flag_count(x, y) > 0 {
+            return 0.0;
+        }
+
+        number_distribution(&self.neighbour_probabilities(x, y, probabilities))[0]
+    }
+
+    /// Chance that the number shown lets a strategy deduce something
+    fn progress_chance(&self, x: u32, y: u32, probabilities: &[Vec<Option<f64>>]) -> f64 {
+        let flags = self.get_surrounding_flag_count(x, y);
+
+        number_distribution(&self.neighbour_probabilities(x, y, probabilities))
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, p)| p > MIN_VALUE_PROBABILITY)
+            .map(|(hidden_mines, p)| {
+                let number = flags + hidden_mines as u8;
+                if number == 0 {
+                    // Empty cells open their whole surrounding
+                    return p;
+                }
+
+                let mut hypothetical = self.branch();
+                hypothetical.set_state(x, y, CellState::Revealed(Cell::Number(number)));
+
+                let view = SolverView::new(&hypothetical);
+                let progress = self
+                    .config
+                    .get_strategies()
+                    .iter()
+                    .any(|strategy| strategy.solve(&view).success());
+
+                if progress { p } else { 0.0 }
+            })
+            .sum()
+    }
+
+    fn neighbour_probabilities(
+        &self,
+        x: u32,
+        y: u32,
+        probabilities: &[Vec<Option<f64>>],
+    ) -> Vec<f64> {
+        self.surrounding_fields(x, y, None)
+            .filter_map(|(nx, ny)| probabilities[nx as usize][ny as usize])
+            .collect()
+    }
+}
+
+/// Distribution of the number of mines among cells with independent mine probabilities
+fn number_distribution(probabilities: &[f64]) -> Vec<f64> {
+    let mut distribution = vec![1.0];
+    for &p in probabilities {
+        let mut next = vec![0.0; distribution.len() + 1];
+        for (count, &q) in distribution.iter().enumerate() {
+            next[count] += q * (1.0 - p);
+            next[count + 1] += q * p;
+        }
+        distribution = next;
+    }
+    distribution
+}
+
+fn sort_by_score(suggestions: &mut [GuessSuggestion], key: impl Fn(&GuessSuggestion) -> f64) {
+    suggestions.sort_by(|a, b| key(b).total_cmp(&key(a)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, Mines, create_solver};
+
+    fn fifty_fifty_field() -> DefinedField {
+        // The mine in the top right corner can't be told apart from the cell below it
+        let mut field = DefinedField::new(5, 2, Mines::Count(1)).unwrap();
+        field.initialize(vec![(4, 0)]);
+        field.set_start_cell(0, 0);
+        field
+    }
+
+    #[test]
+    fn test_fifty_fifty() {
+        let mut solver = create_solver(&fifty_fifty_field());
+        solver.solve().unwrap();
+        assert!(!solver.is_solved());
+
+        let suggestion = solver.suggest_guess().expect("Should suggest a guess");
+
+        assert!([(4, 0), (4, 1)].contains(&suggestion.position));
+        assert!((suggestion.survival_probability - 0.5).abs() < 1e-9);
+        assert_eq!(suggestion.opening_probability, 0.0);
+        // If the guess survives, the revealed 1 always identifies the mine
+        assert!((suggestion.progress_probability - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ratings_sorted() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
+
+        let ratings = solver.rate_guesses();
+
+        assert!(ratings.len() > PROGRESS_CANDIDATES);
+        let (checked, rest) = ratings.split_at(PROGRESS_CANDIDATES);
+        assert!(checked.windows(2).all(|w| w[0].score >= w[1].score));
+        // The unchecked cells keep their order by survival, behind the checked ones
+        assert!(
+            rest.windows(2)
+                .all(|w| w[0].survival_probability >= w[1].survival_probability)
+        );
+        let least_safe_checked = checked
+            .iter()
+            .map(|r| r.survival_probability)
+            .fold(f64::INFINITY, f64::min);
+        assert!(
+            rest.iter()
+                .all(|r| r.survival_probability <= least_safe_checked)
+        );
+        for rating in &ratings {
+            assert!(rating.progress_probability <= rating.survival_probability + 1e-9);
+            assert!(rating.opening_probability <= rating.progress_probability + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_number_distribution() {
+        let distribution = number_distribution(&[0.5, 0.5]);
+
+        assert_eq!(distribution, vec![0.25, 0.5, 0.25]);
+    }
+}
@@ -0,0 +1,284 @@
+use super::{CellState, Solver};
+use crate::Cell;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/*
+Incremental bookkeeping:
+
+Every state change of the solver goes through Solver::set_state, which keeps
+- the number of revealed and flagged cells
+- the number of hidden and flagged neighbours of every cell (reduced counts without rescanning)
+- the frontier: revealed numbers which still border hidden cells
+- a log of all changed cells
+up to date.
+
+Each configured strategy remembers the length of the change log when it last ran.
+Local strategies use this to only revisit the numbers near cells that changed since then,
+their result elsewhere can't have changed.
+
+The neighbour counts are stored per column and shared copy-on-write with snapshots and branches.
+Those start with an empty change log, so every strategy looks at the whole frontier on its first run there.
+*/
+
+#[derive(Clone)]
+pub(super) struct IncrementalState {
+    pub(super) revealed: u32,
+    pub(super) flagged: u32,
+    hidden_neighbours: Vec<Arc<Vec<u8>>>,
+    flagged_neighbours: Vec<Arc<Vec<u8>>>,
+    /// Stored as (y, x) so iteration matches the order of sorted_fields
+    frontier: BTreeSet<(u32, u32)>,
+    changes: Vec<(u32, u32)>,
+    /// Change log length at the last run of each configured strategy
+    strategy_marks: Vec<Option<usize>>,
+    /// Mark of the strategy currently running, `None` outside of solving steps
+    since: Option<usize>,
+}
+
+impl IncrementalState {
+    pub(super) fn new(width: u32, height: u32, strategy_count: usize) -> Self {
+        let span = |v: u32, size: u32| 1 + (v > 0) as u8 + (v + 1 < size) as u8;
+        let hidden_neighbours = (0..width)
+            .map(|x| {
+                Arc::new(
+                    (0..height)
+                        .map(|y| span(x, width) * span(y, height) - 1)
+                        .collect(),
+                )
+            })
+            .collect();
+
+        IncrementalState {
+            revealed: 0,
+            flagged: 0,
+            hidden_neighbours,
+            flagged_neighbours: (0..width)
+                .map(|_| Arc::new(vec![0; height as usize]))
+                .collect(),
+            frontier: BTreeSet::new(),
+            changes: Vec::new(),
+            strategy_marks: vec![None; strategy_count],
+            since: None,
+        }
+    }
+
+    /// Copy sharing the neighbour counts, with an empty change log and no marks
+    /// for `strategy_count` strategies, so each of them scans the whole frontier next time
+    pub(super) fn branch(&self, strategy_count: usize) -> Self {
+        IncrementalState {
+            revealed: self.revealed,
+            flagged: self.flagged,
+            hidden_neighbours: self.hidden_neighbours.clone(),
+            flagged_neighbours: self.flagged_neighbours.clone(),
+            frontier: self.frontier.clone(),
+            changes: Vec::new(),
+            strategy_marks: vec![None; strategy_count],
+            since: None,
+        }
+    }
+
+    pub(super) fn hidden_neighbours(&self, x: u32, y: u32) -> u8 {
+        self.hidden_neighbours[x as usize][y as usize]
+    }
+
+    pub(super) fn flagged_neighbours(&self, x: u32, y: u32) -> u8 {
+        self.flagged_neighbours[x as usize][y as usize]
+    }
+
+    /// Remembers where the change log stood when the strategy at `index` started
+    pub(super) fn begin_strategy(&mut self, index: usize) {
+        self.since = self.strategy_marks[index];
+        self.strategy_marks[index] = Some(self.changes.len());
+    }
+
+    pub(super) fn end_strategies(&mut self) {
+        self.since = None;
+    }
+}
+
+fn counts(state: &CellState) -> (u32, u32) {
+    match state {
+        CellState::Hidden(_) => (0, 0),
+        CellState::Revealed(_) => (1, 0),
+        CellState::Flagged(_) => (0, 1),
+    }
+}
+
+impl Solver {
+    /// Replaces the state of a cell and updates all cached counts
+    pub(super) fn set_state(&mut self, x: u32, y: u32, state: CellState) {
+        let (old_revealed, old_flagged) = counts(self.get_state(x, y));
+        let old_hidden = matches!(self.get_state(x, y), CellState::Hidden(_));
+        let (new_revealed, new_flagged) = counts(&state);
+        let new_hidden = matches!(state, CellState::Hidden(_));
+
+        if matches!(self.get_state(x, y), CellState::Revealed(Cell::Number(_))) {
+            self.incremental.frontier.remove(&(y, x));
+        }
+        Arc::make_mut(&mut self.state[x as usize])[y as usize] = state;
+
+        let tracking = &mut self.incremental;
+        tracking.revealed = tracking.revealed + new_revealed - old_revealed;
+        tracking.flagged = tracking.flagged + new_flagged - old_flagged;
+        tracking.changes.push((x, y));
+
+        for (nx, ny) in self.surrounding_fields(x, y, None) {
+            let tracking = &mut self.incremental;
+            if new_hidden != old_hidden {
+                let hidden =
+                    &mut Arc::make_mut(&mut tracking.hidden_neighbours[nx as usize])[ny as usize];
+                *hidden = *hidden + new_hidden as u8 - old_hidden as u8;
+            }
+            if new_flagged != old_flagged {
+                let flagged =
+                    &mut Arc::make_mut(&mut tracking.flagged_neighbours[nx as usize])[ny as usize];
+                *flagged = *flagged + new_flagged as u8 - old_flagged as u8;
+            }
+
+            self.update_frontier(nx, ny);
+        }
+        self.update_frontier(x, y);
+    }
+
+    fn update_frontier(&mut self, x: u32, y: u32) {
+        // Only revealed numbers can be part of the frontier, this skips the set for the bulk of an opening
+        if !matches!(self.get_state(x, y), CellState::Revealed(Cell::Number(_))) {
+            return;
+        }
+
+        if self.incremental.hidden_neighbours(x, y) > 0 {
+            self.incremental.frontier.insert((y, x));
+        } else {
+            self.incremental.frontier.remove(&(y, x));
+        }
+    }
+
+    /// All revealed numbers which still border hidden cells, in the order of sorted_fields
+    pub(super) fn frontier(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.incremental.frontier.iter().map(|&(y, x)| (x, y))
+    }
+
+    /// Frontier numbers within `range` of a cell that changed since the running strategy last ran.
+    /// Outside of solving steps, or on the first run of a strategy, this is the whole frontier.
+    pub(super) fn dirty_frontier(&self, range: u8) -> Vec<(u32, u32)> {
+        let Some(since) = self.incremental.since else {
+            return self.frontier().collect();
+        };
+
+        let mut dirty = BTreeSet::new();
+        for &(x, y) in &self.incremental.changes[since..] {
+            let area = self.surrounding_fields(x, y, Some(range)).chain([(x, y)]);
+            for (nx, ny) in area {
+                if self.incremental.frontier.contains(&(ny, nx)) {
+                    dirty.insert((ny, nx));
+                }
+            }
+        }
+
+        dirty.into_iter().map(|(y, x)| (x, y)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, MineSweeperField, SolverConfig, create_solver};
+
+    fn assert_matches_rescan(solver: &Solver) {
+        let mut revealed = 0;
+        let mut flagged = 0;
+        for (x, y) in solver.sorted_fields() {
+            match solver.get_state(x, y) {
+                CellState::Revealed(_) => revealed += 1,
+                CellState::Flagged(_) => flagged += 1,
+                CellState::Hidden(_) => {}
+            }
+
+            let neighbours: Vec<_> = solver.surrounding_fields(x, y, None).collect();
+            let hidden = neighbours
+                .iter()
+                .filter(|&&(nx, ny)| matches!(solver.get_state(nx, ny), CellState::Hidden(_)))
+                .count();
+            let flags = neighbours
+                .iter()
+                .filter(|&&(nx, ny)| matches!(solver.get_state(nx, ny), CellState::Flagged(_)))
+                .count();
+            assert_eq!(solver.incremental.hidden_neighbours(x, y) as usize, hidden);
+            assert_eq!(solver.incremental.flagged_neighbours(x, y) as usize, flags);
+        }
+
+        assert_eq!(solver.incremental.revealed, revealed);
+        assert_eq!(solver.incremental.flagged, flagged);
+
+        let frontier: Vec<_> = solver
+            .sorted_fields()
+            .filter(|&(x, y)| {
+                matches!(solver.get_state(x, y), CellState::Revealed(Cell::Number(_)))
+                    && solver.incremental.hidden_neighbours(x, y) > 0
+            })
+            .collect();
+        assert_eq!(solver.frontier().collect::<Vec<_>>(), frontier);
+    }
+
+    #[test]
+    fn test_counts_match_rescan() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
+        assert_matches_rescan(&solver);
+
+        solver.solve().unwrap();
+        assert!(solver.is_solved());
+        assert_matches_rescan(&solver);
+        assert_eq!(
+            solver.revealed_count(),
+            field.get_width() * field.get_height() - field.get_mines()
+        );
+        assert_eq!(solver.get_remaining_mines(), 0);
+    }
+
+    #[test]
+    fn test_dirty_frontier_is_local() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        let mut solver = Solver::new(&field, &SolverConfig::default());
+        solver.open_start_cell().unwrap();
+
+        // Outside of a solving step everything counts as dirty
+        let frontier: Vec<_> = solver.frontier().collect();
+        assert!(!frontier.is_empty());
+        assert_eq!(solver.dirty_frontier(1), frontier);
+
+        // Nothing changed since the strategy's last run
+        solver.incremental.begin_strategy(0);
+        solver.incremental.begin_strategy(0);
+        assert!(solver.dirty_frontier(1).is_empty());
+
+        // Flagging a mine only dirties the numbers around it
+        let (mx, my) = solver
+            .sorted_fields()
+            .find(|&(x, y)| {
+                matches!(solver.get_state(x, y), CellState::Hidden(Some(Cell::Mine)))
+                    && frontier
+                        .iter()
+                        .any(|&(fx, fy)| fx.abs_diff(x) <= 1 && fy.abs_diff(y) <= 1)
+            })
+            .expect("Should have a mine next to the opening");
+        solver.incremental.end_strategies();
+        solver.incremental.begin_strategy(1);
+        solver.flag_cell(mx, my).unwrap();
+        solver.incremental.begin_strategy(1);
+
+        let dirty = solver.dirty_frontier(1);
+        assert!(!dirty.is_empty());
+        assert!(
+            dirty
+                .iter()
+                .all(|&(x, y)| x.abs_diff(mx) <= 1 && y.abs_diff(my) <= 1)
+        );
+    }
+}
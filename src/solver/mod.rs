@@ -1,11 +1,43 @@
+mod ambiguity;
+mod budget;
 mod cell_state;
+mod clicks;
+mod config;
+mod dependencies;
+mod difficulty;
+mod error;
+mod explanation;
 mod findings;
+mod guess;
+mod incremental;
+mod probability;
+#[cfg(test)]
+mod reference;
+mod snapshot;
 mod solving_api;
 mod solving_helpers;
 mod solving_utils;
+mod stats;
 mod strategy;
+mod view;
+mod visible;
 
 pub(crate) use cell_state::CellState;
 
+pub use ambiguity::{AmbiguityReport, AmbiguousRegion};
+pub use budget::{CancellationToken, GiveUpReason, SolverBudget};
+pub use clicks::{ClickAction, ClickSequence};
+pub use config::SolverConfig;
+pub use dependencies::{DeductionGraph, Dependency};
+pub use difficulty::{Difficulty, DifficultyTier, difficulty};
+pub use error::SolverError;
+pub use explanation::{ConstraintCell, Explanation, Rule};
 pub use findings::Finding;
-pub use solving_api::{Solver, create_solver, is_solvable};
+pub use guess::GuessSuggestion;
+pub use snapshot::SolverSnapshot;
+pub use solving_api::{SolveOutcome, Solver, SolvingSteps, create_solver, is_solvable, try_solve};
+pub(crate) use stats::SearchCounters;
+pub use stats::{SolverStats, StrategyStats};
+pub use strategy::{SolvingStrategy, Strategy};
+pub use view::SolverView;
+pub use visible::VisibleCell;
@@ -0,0 +1,353 @@
+use super::strategy::sat_solver::{
+    ComponentSolutions, Constraint, build_constraints, enumerate_solutions,
+    find_independent_components,
+};
+use super::{CellState, Solver};
+use std::collections::HashSet;
+
+/*
+Exact mine probabilities:
+
+Every independent component is enumerated once, counting its solutions per number of placed mines.
+Hidden cells which don't border any revealed number (interior cells) are unconstrained,
+the remaining mines can be placed there in binomial(interior, remaining - component mines) ways.
+A global layout is one solution per component plus an interior placement, so each combination
+of component mine counts is weighted by the product of the solution counts and that binomial.
+*/
+
+/// Enumerated components of the current visible state, see [`Solver::get_mine_probabilities`].
+pub(crate) struct ProbabilityModel {
+    width: u32,
+    height: u32,
+    remaining_mines: u32,
+    components: Vec<Vec<(u32, u32)>>,
+    constraints: Vec<Vec<Constraint>>,
+    solutions: Vec<ComponentSolutions>,
+    interior: Vec<(u32, u32)>,
+}
+
+impl Solver {
+    /// Probability of every hidden cell being a mine, given only the visible state.
+    /// Indexed as `[x][y]`, `None` for revealed and flagged cells.
+    pub fn get_mine_probabilities(&self) -> Vec<Vec<Option<f64>>> {
+        self.probability_model().probabilities()
+    }
+
+    pub(crate) fn probability_model(&self) -> ProbabilityModel {
+        let remaining_mines = self.get_remaining_mines();
+        let components = find_independent_components(self);
+
+        let border: HashSet<(u32, u32)> = components.iter().flatten().copied().collect();
+        let interior: Vec<(u32, u32)> = self
+            .sorted_fields()
+            .filter(|&(x, y)| matches!(self.get_state(x, y), CellState::Hidden(_)))
+            .filter(|field| !border.contains(field))
+            .collect();
+
+        let constraints: Vec<Vec<Constraint>> = components
+            .iter()
+            .map(|component| build_constraints(self, component))
+            .collect();
+        let solutions = components
+            .iter()
+            .zip(&constraints)
+            .map(|(component, constraints)| {
+                enumerate_solutions(component, constraints, remaining_mines)
+            })
+            .collect();
+
+        ProbabilityModel {
+            width: self.width,
+            height: self.height,
+            remaining_mines,
+            components,
+            constraints,
+            solutions,
+            interior,
+        }
+    }
+}
+
+impl ProbabilityModel {
+    pub(crate) fn probabilities(&self) -> Vec<Vec<Option<f64>>> {
+        self.to_grid(&self.solutions, &self.interior)
+    }
+
+    /// Probabilities under the assumption that `field` is safe, `field` itself is reported as `None`
+    pub(crate) fn probabilities_assuming_safe(&self, field: (u32, u32)) -> Vec<Vec<Option<f64>>> {
+        let mut solutions = self.solutions.clone();
+        let mut interior = self.interior.clone();
+
+        match self.components.iter().position(|c| c.contains(&field)) {
+            Some(i) => {
+                let mut constraints = self.constraints[i].clone();
+                constraints.push(Constraint {
+                    mine_count: 0,
+                    fields: vec![field],
+                });
+                solutions[i] =
+                    enumerate_solutions(&self.components[i], &constraints, self.remaining_mines);
+            }
+            None => interior.retain(|&f| f != field),
+        }
+
+        let mut grid = self.to_grid(&solutions, &interior);
+        grid[field.0 as usize][field.1 as usize] = None;
+        grid
+    }
+
+    fn to_grid(
+        &self,
+        solutions: &[ComponentSolutions],
+        interior: &[(u32, u32)],
+    ) -> Vec<Vec<Option<f64>>> {
+        let mut probabilities = vec![vec![None; self.height as usize]; self.width as usize];
+
+        let (component_probabilities, interior_probability) =
+            combine_solutions(solutions, interior.len() as u32, self.remaining_mines);
+
+        for (component, cell_probabilities) in self.components.iter().zip(component_probabilities) {
+            for (&(x, y), probability) in component.iter().zip(cell_probabilities) {
+                probabilities[x as usize][y as usize] = Some(probability);
+            }
+        }
+        for &(x, y) in interior {
+            probabilities[x as usize][y as usize] = Some(interior_probability);
+        }
+
+        probabilities
+    }
+}
+
+/// Combines the per-component solutions with the interior cells and the global mine count.
+/// Returns the mine probability of every component cell and the probability of a single interior cell.
+pub(crate) fn combine_solutions(
+    solutions: &[ComponentSolutions],
+    interior: u32,
+    remaining_mines: u32,
+) -> (Vec<Vec<f64>>, f64) {
+    // Scaling a component's counts doesn't change any probability, but keeps big counts in range
+    let distributions: Vec<Vec<f64>> = solutions
+        .iter()
+        .map(|s| normalize(s.solution_counts.clone()))
+        .collect();
+
+    let weights = interior_weights(interior, remaining_mines);
+    let weight = |mines: usize| weights.get(mines).copied().unwrap_or(0.0);
+
+    // prefix[i] / suffix[i]: mine distribution of all components before / from i
+    let mut prefix = vec![vec![1.0]];
+    for distribution in &distributions {
+        let next = convolve(prefix.last().unwrap(), distribution);
+        prefix.push(next);
+    }
+    let mut suffix = vec![vec![1.0]; distributions.len() + 1];
+    for i in (0..distributions.len()).rev() {
+        suffix[i] = convolve(&suffix[i + 1], &distributions[i]);
+    }
+
+    let all = &prefix[distributions.len()];
+    let total: f64 = all.iter().enumerate().map(|(k, c)| c * weight(k)).sum();
+
+    if total <= 0.0 {
+        // Inconsistent state, nothing sensible to report
+        let zeros = solutions
+            .iter()
+            .map(|s| vec![0.0; s.mine_counts.first().map_or(0, |m| m.len())])
+            .collect();
+        return (zeros, 0.0);
+    }
+
+    let component_probabilities = solutions
+        .iter()
+        .enumerate()
+        .map(|(i, solution)| {
+            let others = convolve(&prefix[i], &suffix[i + 1]);
+            let scale = max_value(&solution.solution_counts);
+            let size = solution.mine_counts.first().map_or(0, |m| m.len());
+
+            (0..size)
+                .map(|cell| {
+                    let mut sum = 0.0;
+                    for (k, mine_counts) in solution.mine_counts.iter().enumerate() {
+                        if mine_counts[cell] == 0.0 {
+                            continue;
+                        }
+                        for (other_k, other_count) in others.iter().enumerate() {
+                            sum += mine_counts[cell] / scale * other_count * weight(k + other_k);
+                        }
+                    }
+                    sum / total
+                })
+                .collect()
+        })
+        .collect();
+
+    let interior_probability = if interior == 0 {
+        0.0
+    } else {
+        all.iter()
+            .enumerate()
+            .filter(|&(k, _)| k as u32 <= remaining_mines)
+            .map(|(k, c)| c * weight(k) * (remaining_mines - k as u32) as f64 / interior as f64)
+            .sum::<f64>()
+            / total
+    };
+
+    (component_probabilities, interior_probability)
+}
+
+/// `weights[k]` is proportional to binomial(interior, remaining - k),
+/// the number of ways to place the mines not used by the components.
+fn interior_weights(interior: u32, remaining_mines: u32) -> Vec<f64> {
+    let ln_factorial: Vec<f64> = std::iter::once(0.0)
+        .chain((1..=interior).scan(0.0, |sum, n| {
+            *sum += (n as f64).ln();
+            Some(*sum)
+        }))
+        .collect();
+    let ln_binomial = |n: u32, k: u32| {
+        ln_factorial[n as usize] - ln_factorial[k as usize] - ln_factorial[(n - k) as usize]
+    };
+
+    let ln_weights: Vec<Option<f64>> = (0..=remaining_mines)
+        .map(|k| {
+            let left = remaining_mines - k;
+            (left <= interior).then(|| ln_binomial(interior, left))
+        })
+        .collect();
+
+    let max = ln_weights
+        .iter()
+        .flatten()
+        .copied()
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    ln_weights
+        .into_iter()
+        .map(|w| w.map_or(0.0, |w| (w - max).exp()))
+        .collect()
+}
+
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        if *x == 0.0 {
+            continue;
+        }
+        for (j, y) in b.iter().enumerate() {
+            result[i + j] += x * y;
+        }
+    }
+    result
+}
+
+fn max_value(values: &[f64]) -> f64 {
+    values.iter().copied().fold(0.0, f64::max)
+}
+
+fn normalize(mut values: Vec<f64>) -> Vec<f64> {
+    let max = max_value(&values);
+    if max > 0.0 {
+        values.iter_mut().for_each(|v| *v /= max);
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, create_solver};
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "expected {} to be close to {}", a, b);
+    }
+
+    #[test]
+    fn test_interior_weighting() {
+        // Cells a, b, c with constraints a + b = 1 and b + c = 1:
+        // either only b is a mine, or a and c are mines
+        let solutions = ComponentSolutions {
+            solution_counts: vec![0.0, 1.0, 1.0],
+            mine_counts: vec![
+                vec![0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0],
+                vec![1.0, 0.0, 1.0],
+            ],
+            safe_counts: vec![
+                vec![0.0, 0.0, 0.0],
+                vec![1.0, 0.0, 1.0],
+                vec![0.0, 1.0, 0.0],
+            ],
+        };
+
+        // 2 mines and 3 interior cells: binomial(3, 1) = 3 layouts with b, binomial(3, 0) = 1 with a and c
+        let (components, interior) = combine_solutions(&[solutions], 3, 2);
+
+        assert_close(components[0][0], 0.25);
+        assert_close(components[0][1], 0.75);
+        assert_close(components[0][2], 0.25);
+        assert_close(interior, 0.25);
+    }
+
+    #[test]
+    fn test_independent_components() {
+        // Two separate 50/50s, one mine left for the interior of 2 cells
+        let fifty_fifty = ComponentSolutions {
+            solution_counts: vec![0.0, 2.0],
+            mine_counts: vec![vec![0.0, 0.0], vec![1.0, 1.0]],
+            safe_counts: vec![vec![0.0, 0.0], vec![1.0, 1.0]],
+        };
+
+        let (components, interior) = combine_solutions(&[fifty_fifty.clone(), fifty_fifty], 2, 3);
+
+        for probabilities in components {
+            assert_close(probabilities[0], 0.5);
+            assert_close(probabilities[1], 0.5);
+        }
+        assert_close(interior, 0.5);
+    }
+
+    #[test]
+    fn test_probabilities_sum_to_remaining_mines() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
+
+        let probabilities = solver.get_mine_probabilities();
+        let mut sum = 0.0;
+        for (x, y) in solver.sorted_fields() {
+            let hidden = matches!(solver.get_state(x, y), CellState::Hidden(_));
+            let probability = probabilities[x as usize][y as usize];
+
+            assert_eq!(hidden, probability.is_some());
+            if let Some(p) = probability {
+                assert!((0.0..=1.0).contains(&p));
+                sum += p;
+            }
+        }
+
+        assert_close(sum, solver.get_remaining_mines() as f64);
+    }
+
+    #[test]
+    fn test_forced_cells_are_certain() {
+        let field = DefinedField::from_file("generated/patterns/1-2-1-R.minesweeper")
+            .expect("Failed to load pattern file");
+
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
+
+        let probabilities = solver.get_mine_probabilities();
+        let finding = crate::solver::strategy::sat_solver::solve(&solver);
+
+        for &(x, y) in finding.get_mine_fields() {
+            assert_close(probabilities[x as usize][y as usize].unwrap(), 1.0);
+        }
+        for &(x, y) in finding.get_safe_fields() {
+            assert_close(probabilities[x as usize][y as usize].unwrap(), 0.0);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use super::{CellState, Solver};
+use crate::Cell;
+use std::collections::HashSet;
+
+/*
+Reference solver:
+
+A slow solver for small boards which is easy to check by hand, the strategies are tested against it.
+It only looks at the visible board, just like the strategies:
+- every hidden cell bordering a revealed cell is tried as mine and as safe by plain backtracking,
+  a layout is kept if every revealed number sees exactly its number of mines
+- hidden cells without revealed neighbours are interchangeable, a layout with k bordering mines
+  leaves remaining - k mines for them, which has to fit into their count
+A cell is proven safe (or a mine) if it is safe (or a mine) in every kept layout.
+*/
+
+/// Cells every layout consistent with the visible board agrees on
+#[derive(Debug, Default)]
+pub(super) struct ReferenceDeductions {
+    pub(super) safe: HashSet<(u32, u32)>,
+    pub(super) mines: HashSet<(u32, u32)>,
+}
+
+impl ReferenceDeductions {
+    pub(super) fn is_empty(&self) -> bool {
+        self.safe.is_empty() && self.mines.is_empty()
+    }
+}
+
+/// Revealed number over the indices of its hidden neighbours
+struct Constraint {
+    cells: Vec<usize>,
+    mines: u32,
+}
+
+struct Search {
+    constraints: Vec<Constraint>,
+    remaining_mines: u32,
+    interior: u32,
+    layout: Vec<bool>,
+    can_be_mine: Vec<bool>,
+    can_be_safe: Vec<bool>,
+    interior_can_be_mine: bool,
+    interior_can_be_safe: bool,
+}
+
+impl Search {
+    /// Checks the constraints on the first `assigned` cells of the layout
+    fn consistent(&self, assigned: usize) -> bool {
+        self.constraints.iter().all(|constraint| {
+            let mines = constraint
+                .cells
+                .iter()
+                .filter(|&&cell| cell < assigned && self.layout[cell])
+                .count() as u32;
+            let open = constraint
+                .cells
+                .iter()
+                .filter(|&&cell| cell >= assigned)
+                .count() as u32;
+            mines <= constraint.mines && mines + open >= constraint.mines
+        })
+    }
+
+    fn search(&mut self, assigned: usize) {
+        if !self.consistent(assigned) {
+            return;
+        }
+        if assigned == self.layout.len() {
+            self.record();
+            return;
+        }
+        for mine in [false, true] {
+            self.layout[assigned] = mine;
+            self.search(assigned + 1);
+        }
+    }
+
+    fn record(&mut self) {
+        let mines = self.layout.iter().filter(|&&mine| mine).count() as u32;
+        let Some(interior_mines) = self.remaining_mines.checked_sub(mines) else {
+            return;
+        };
+        if interior_mines > self.interior {
+            return;
+        }
+
+        for (cell, &mine) in self.layout.iter().enumerate() {
+            if mine {
+                self.can_be_mine[cell] = true;
+            } else {
+                self.can_be_safe[cell] = true;
+            }
+        }
+        self.interior_can_be_mine |= interior_mines > 0;
+        self.interior_can_be_safe |= interior_mines < self.interior;
+    }
+}
+
+/// Every hidden cell the visible board of `solver` proves to be safe or a mine.
+/// Exponential in the number of hidden cells bordering revealed ones, only meant for small boards.
+pub(super) fn reference_deductions(solver: &Solver) -> ReferenceDeductions {
+    let is_hidden = |x: u32, y: u32| matches!(solver.get_state(x, y), CellState::Hidden(_));
+    let revealed_number = |x: u32, y: u32| match solver.get_state(x, y) {
+        CellState::Revealed(Cell::Number(number)) => Some(*number as u32),
+        CellState::Revealed(_) => Some(0),
+        _ => None,
+    };
+
+    let (bordering, interior): (Vec<_>, Vec<_>) = solver
+        .sorted_fields()
+        .filter(|&(x, y)| is_hidden(x, y))
+        .partition(|&(x, y)| {
+            solver
+                .surrounding_fields(x, y, None)
+                .any(|(nx, ny)| revealed_number(nx, ny).is_some())
+        });
+
+    let constraints = solver
+        .sorted_fields()
+        .filter_map(|(x, y)| {
+            let number = revealed_number(x, y)?;
+            let flags = solver
+                .surrounding_fields(x, y, None)
+                .filter(|&(nx, ny)| matches!(solver.get_state(nx, ny), CellState::Flagged(_)))
+                .count() as u32;
+            let cells: Vec<usize> = solver
+                .surrounding_fields(x, y, None)
+                .filter_map(|neighbour| bordering.iter().position(|&cell| cell == neighbour))
+                .collect();
+            Some(Constraint {
+                cells,
+                mines: number.saturating_sub(flags),
+            })
+        })
+        .collect();
+
+    let mut search = Search {
+        constraints,
+        remaining_mines: solver.get_remaining_mines(),
+        interior: interior.len() as u32,
+        layout: vec![false; bordering.len()],
+        can_be_mine: vec![false; bordering.len()],
+        can_be_safe: vec![false; bordering.len()],
+        interior_can_be_mine: false,
+        interior_can_be_safe: false,
+    };
+    search.search(0);
+
+    let mut deductions = ReferenceDeductions::default();
+    for (index, &cell) in bordering.iter().enumerate() {
+        match (search.can_be_mine[index], search.can_be_safe[index]) {
+            (true, false) => {
+                deductions.mines.insert(cell);
+            }
+            (false, true) => {
+                deductions.safe.insert(cell);
+            }
+            _ => {}
+        }
+    }
+    for &cell in &interior {
+        match (search.interior_can_be_mine, search.interior_can_be_safe) {
+            (true, false) => {
+                deductions.mines.insert(cell);
+            }
+            (false, true) => {
+                deductions.safe.insert(cell);
+            }
+            _ => {}
+        }
+    }
+    deductions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, MineSweeperField, Mines, RandomField, create_solver};
+
+    /// Steps through `field` and checks every finding against the reference.
+    /// Once the solver is stuck, the reference mustn't prove anything either.
+    fn assert_matches_reference(field: &impl MineSweeperField) {
+        let mut solver = create_solver(field);
+        assert!(solver.step().is_some(), "The start cell should open");
+
+        loop {
+            let reference = reference_deductions(&solver);
+            let board = solver.format_field_state();
+
+            let Some(finding) = solver.step() else {
+                assert_eq!(solver.get_error(), None, "Solver failed on {}", board);
+                if !solver.is_solved() {
+                    assert!(
+                        reference.is_empty(),
+                        "Solver is stuck, but the reference proves {:?} safe and {:?} mines on {}",
+                        reference.safe,
+                        reference.mines,
+                        board
+                    );
+                }
+                return;
+            };
+
+            for cell in finding.get_safe_fields() {
+                assert!(
+                    reference.safe.contains(cell),
+                    "{:?} revealed {:?} without proof on {}",
+                    finding.get_strategy(),
+                    cell,
+                    board
+                );
+            }
+            for cell in finding.get_mine_fields() {
+                assert!(
+                    reference.mines.contains(cell),
+                    "{:?} flagged {:?} without proof on {}",
+                    finding.get_strategy(),
+                    cell,
+                    board
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reference_on_fifty_fifty() {
+        let mut field = DefinedField::new(5, 2, Mines::Count(1)).unwrap();
+        field.initialize(vec![(4, 0)]);
+        field.set_start_cell(0, 0);
+        let mut solver = create_solver(&field);
+        solver.step();
+
+        assert!(reference_deductions(&solver).is_empty());
+    }
+
+    #[test]
+    fn test_reference_uses_mine_count() {
+        // The 1 at (1, 0) proves the mine at (2, 0), which is the only one
+        let mut field = DefinedField::new(5, 1, Mines::Count(1)).unwrap();
+        field.initialize(vec![(2, 0)]);
+        field.set_start_cell(0, 0);
+        let mut solver = create_solver(&field);
+        solver.step();
+
+        let reference = reference_deductions(&solver);
+        assert_eq!(reference.mines, HashSet::from([(2, 0)]));
+        assert_eq!(reference.safe, HashSet::from([(3, 0), (4, 0)]));
+    }
+
+    #[test]
+    fn test_solver_matches_reference() {
+        let sizes = [(5, 5, 5), (6, 6, 8), (8, 8, 12), (9, 7, 14)];
+
+        for (width, height, mines) in sizes {
+            for _ in 0..25 {
+                let field = RandomField::new(width, height, Mines::Count(mines)).unwrap();
+                let (x, y) = field.get_start_cell();
+                if field.get_cell(x, y) != &Cell::Empty {
+                    continue;
+                }
+                assert_matches_reference(&field);
+            }
+        }
+    }
+}
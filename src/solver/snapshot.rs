@@ -0,0 +1,382 @@
+use super::budget::GiveUpReason;
+use super::incremental::IncrementalState;
+use super::stats::{SearchCounters, SolverStats};
+use super::{CellState, Finding, Solver, SolverError};
+use crate::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/*
+Snapshots and branches:
+
+The board is stored as one shared column per x, the neighbour counts of the incremental state likewise.
+Copying a solver only copies the column handles, the frontier and the step handles.
+The first change to a column after a copy duplicates that column alone (copy-on-write),
+so a branch which assumes a few cells costs a few columns, not the whole board.
+- a snapshot saves the position of a solver, restoring it undoes all later steps
+- a branch is a separate solver on the same position which only knows what a player sees.
+  Assumptions are placed on it like a player would, a flag on any hidden cell
+  or a number on any hidden cell, checked against the visible numbers only.
+  The strategies then show what follows, the original solver stays untouched.
+A snapshot remembers the board it was taken on by a hash of the starting board, mine count
+and start cell. Branches get their own id, so a branch can't restore the hidden contents of its origin.
+*/
+
+/// Saved position of a solver, see [`Solver::snapshot`].
+#[derive(Clone)]
+pub struct SolverSnapshot {
+    width: u32,
+    height: u32,
+    mines: u32,
+    board_id: u64,
+    state: Vec<Arc<Vec<CellState>>>,
+    incremental: IncrementalState,
+    solving_steps: Vec<Arc<Finding>>,
+    error: Option<SolverError>,
+    gave_up: Option<GiveUpReason>,
+}
+
+impl SolverSnapshot {
+    /// Number of solving steps made up to the snapshot, including the start
+    pub fn get_step_count(&self) -> usize {
+        self.solving_steps.len()
+    }
+}
+
+impl Solver {
+    /// Saves the current position, cheap even on big boards as the board is shared until it changes.
+    pub fn snapshot(&self) -> SolverSnapshot {
+        SolverSnapshot {
+            width: self.width,
+            height: self.height,
+            mines: self.mines,
+            board_id: self.board_id,
+            state: self.state.clone(),
+            incremental: self.incremental.branch(self.config.get_strategies().len()),
+            solving_steps: self.solving_steps.clone(),
+            error: self.error.clone(),
+            gave_up: self.gave_up,
+        }
+    }
+
+    /// Goes back to the position of `snapshot`, including its solving steps.
+    /// The statistics keep counting the work done since.
+    /// The snapshot may come from a solver with other strategies, all of them start over on the whole frontier.
+    /// Errors if the snapshot was taken of another board.
+    pub fn restore(&mut self, snapshot: &SolverSnapshot) -> Result<(), SolverError> {
+        if (snapshot.width, snapshot.height) != (self.width, self.height) {
+            return Err(SolverError::InvalidBoard(format!(
+                "the snapshot is of a {}x{} board, not {}x{}",
+                snapshot.width, snapshot.height, self.width, self.height
+            )));
+        }
+        if snapshot.mines != self.mines {
+            return Err(SolverError::InvalidBoard(format!(
+                "the snapshot is of a board with {} mines, not {}",
+                snapshot.mines, self.mines
+            )));
+        }
+        if snapshot.board_id != self.board_id {
+            return Err(SolverError::InvalidBoard(
+                "the snapshot is of another board".into(),
+            ));
+        }
+
+        self.state = snapshot.state.clone();
+        self.incremental = snapshot
+            .incremental
+            .branch(self.config.get_strategies().len());
+        self.solving_steps = snapshot.solving_steps.clone();
+        self.error = snapshot.error.clone();
+        self.gave_up = snapshot.gave_up;
+        Ok(())
+    }
+
+    /// A solver on the current position which only knows what a player sees, like [`Solver::from_visible`].
+    /// It starts without solving steps and shares the board with this solver until either changes.
+    /// Place assumptions with [`Solver::assume_mine`] and [`Solver::assume_number`], then ask
+    /// [`Solver::hint`] what follows.
+    pub fn branch(&self) -> Solver {
+        Solver {
+            state: self.state.clone(),
+            width: self.width,
+            height: self.height,
+            mines: self.mines,
+            start_cell: self.start_cell,
+            solving_steps: Vec::new(),
+            config: self.config.clone(),
+            incremental: self.incremental.branch(self.config.get_strategies().len()),
+            error: None,
+            gave_up: None,
+            stats: SolverStats::new(self.stats.strategies.iter().map(|s| s.name.clone())),
+            search_counters: SearchCounters::default(),
+            blind: true,
+            board_id: branch_id(self.board_id),
+        }
+    }
+
+    /// True for solvers created by [`Solver::branch`]
+    pub fn is_branch(&self) -> bool {
+        self.blind
+    }
+
+    /// Flags a hidden cell as a hypothetical mine, only allowed on branches.
+    /// Errors if the cell isn't hidden, or a revealed number would border more flags than it shows.
+    pub fn assume_mine(&mut self, x: u32, y: u32) -> Result<(), SolverError> {
+        self.check_assumption(x, y)?;
+        if self.get_remaining_mines() == 0 {
+            return Err(SolverError::InvalidBoard(format!(
+                "all {} mines are flagged already",
+                self.mines
+            )));
+        }
+        self.flag_cell(x, y)
+    }
+
+    /// Reveals a hidden cell with a hypothetical number (0 for empty), only allowed on branches.
+    /// Nothing around an assumed empty cell gets opened, its neighbours are just known to be safe.
+    /// Errors if the cell isn't hidden, or the number doesn't fit its flags and hidden neighbours.
+    pub fn assume_number(&mut self, x: u32, y: u32, number: u8) -> Result<(), SolverError> {
+        self.check_assumption(x, y)?;
+
+        let flags = self.get_surrounding_flag_count(x, y);
+        if flags > number {
+            return Err(SolverError::InconsistentFlags {
+                x,
+                y,
+                number,
+                flags,
+            });
+        }
+        if number > 8 || flags + self.get_surrounding_unrevealed_count(x, y) < number {
+            return Err(SolverError::InvalidBoard(format!(
+                "({}, {}) can't show a {}",
+                x, y, number
+            )));
+        }
+
+        let cell = match number {
+            0 => Cell::Empty,
+            n => Cell::Number(n),
+        };
+        self.set_state(x, y, CellState::Revealed(cell));
+        Ok(())
+    }
+
+    fn check_assumption(&self, x: u32, y: u32) -> Result<(), SolverError> {
+        if !self.blind {
+            return Err(SolverError::InvalidBoard(
+                "assumptions can only be placed on a branch".into(),
+            ));
+        }
+        if x >= self.width || y >= self.height {
+            return Err(SolverError::InvalidBoard(format!(
+                "({}, {}) is outside of the {}x{} board",
+                x, y, self.width, self.height
+            )));
+        }
+        if !matches!(self.get_state(x, y), CellState::Hidden(_)) {
+            return Err(SolverError::InvalidBoard(format!(
+                "({}, {}) isn't hidden",
+                x, y
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Hash of the board a solver starts on, with the contents of hidden cells if they are known
+pub(super) fn board_id(state: &[Vec<CellState>], mines: u32, start_cell: (u32, u32)) -> u64 {
+    let cell_code = |cell: &Cell| match cell {
+        Cell::Empty => 0,
+        Cell::Mine => 9,
+        Cell::Number(n) => *n,
+    };
+
+    let mut hasher = DefaultHasher::new();
+    (state.len(), mines, start_cell).hash(&mut hasher);
+    for column in state {
+        for cell in column {
+            let code = match cell {
+                CellState::Hidden(None) => (0, 0),
+                CellState::Hidden(Some(cell)) => (1, cell_code(cell)),
+                CellState::Revealed(cell) => (2, cell_code(cell)),
+                CellState::Flagged(cell) => (3, cell_code(cell)),
+            };
+            code.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+/// Id of a branch, which only knows the visible part of the board with `board_id`
+fn branch_id(board_id: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (board_id, "branch").hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, Mines, SolveOutcome, SolverConfig, SolvingStrategy, create_solver};
+
+    fn fifty_fifty_field() -> DefinedField {
+        let mut field = DefinedField::new(5, 2, Mines::Count(1)).unwrap();
+        field.initialize(vec![(4, 0)]);
+        field.set_start_cell(0, 0);
+        field
+    }
+
+    #[test]
+    fn test_restore() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+        let mut solver = create_solver(&field);
+        for _ in 0..5 {
+            solver.step();
+        }
+        let snapshot = solver.snapshot();
+        let board = solver.get_visible_board();
+
+        assert_eq!(solver.solve(), Ok(SolveOutcome::Solved));
+        let steps = solver.get_solving_steps().len();
+
+        solver.restore(&snapshot).unwrap();
+        assert_eq!(solver.get_visible_board(), board);
+        assert_eq!(solver.get_solving_steps().len(), 5);
+        assert_eq!(snapshot.get_step_count(), 5);
+        assert!(!solver.is_solved());
+
+        // The restored solver continues like the original did
+        assert_eq!(solver.solve(), Ok(SolveOutcome::Solved));
+        assert_eq!(solver.get_solving_steps().len(), steps);
+
+        let mut other = create_solver(&fifty_fifty_field());
+        assert!(matches!(
+            other.restore(&snapshot),
+            Err(SolverError::InvalidBoard(_))
+        ));
+    }
+
+    #[test]
+    fn test_restore_checks_the_board() {
+        let snapshot = create_solver(&fifty_fifty_field()).snapshot();
+
+        // Same size and mine count, the mine on the other side
+        let mut field = DefinedField::new(5, 2, Mines::Count(1)).unwrap();
+        field.initialize(vec![(4, 1)]);
+        field.set_start_cell(0, 0);
+        let mut other = create_solver(&field);
+        assert!(matches!(
+            other.restore(&snapshot),
+            Err(SolverError::InvalidBoard(_))
+        ));
+
+        let mut field = DefinedField::new(5, 2, Mines::Count(2)).unwrap();
+        field.initialize(vec![(4, 0), (4, 1)]);
+        field.set_start_cell(0, 0);
+        let mut other = create_solver(&field);
+        assert!(matches!(
+            other.restore(&snapshot),
+            Err(SolverError::InvalidBoard(_))
+        ));
+
+        // A branch doesn't get the hidden contents of its origin
+        let mut branch = create_solver(&fifty_fifty_field()).branch();
+        assert!(matches!(
+            branch.restore(&snapshot),
+            Err(SolverError::InvalidBoard(_))
+        ));
+    }
+
+    #[test]
+    fn test_restore_across_configs() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        let mut simple = Solver::new(&field, &SolverConfig::new(vec![SolvingStrategy::Simple]));
+        simple.solve().unwrap();
+        let snapshot = simple.snapshot();
+
+        // All strategies of the default config scan the board the simple solver got stuck on
+        let mut solver = create_solver(&field);
+        solver.restore(&snapshot).unwrap();
+        assert_eq!(solver.solve(), Ok(SolveOutcome::Solved));
+    }
+
+    #[test]
+    fn test_branch_is_copy_on_write() {
+        let mut solver = create_solver(&fifty_fifty_field());
+        solver.solve().unwrap();
+
+        let mut branch = solver.branch();
+        assert!(branch.is_branch());
+        assert!((0..5).all(|x| Arc::ptr_eq(&solver.state[x], &branch.state[x])));
+
+        branch.assume_mine(4, 0).unwrap();
+        // Only the column of the assumption got copied
+        assert!((0..4).all(|x| Arc::ptr_eq(&solver.state[x], &branch.state[x])));
+        assert!(!Arc::ptr_eq(&solver.state[4], &branch.state[4]));
+
+        assert!(matches!(solver.get_state(4, 0), CellState::Hidden(_)));
+        assert_eq!(solver.get_remaining_mines(), 1);
+        assert_eq!(branch.get_remaining_mines(), 0);
+    }
+
+    #[test]
+    fn test_what_if() {
+        let mut solver = create_solver(&fifty_fifty_field());
+        solver.solve().unwrap();
+
+        // If the top cell is the mine, the bottom one is safe
+        let mut branch = solver.branch();
+        branch.assume_mine(4, 0).unwrap();
+        let hint = branch.hint().expect("The assumption decides the 50/50");
+        assert_eq!(hint.get_safe_fields(), &vec![(4, 1)]);
+
+        // A branch only knows the visible board, so it can't reveal the cell
+        assert_eq!(branch.solve(), Err(SolverError::UnknownCell { x: 4, y: 1 }));
+
+        // If the bottom cell shows a 1, the top one is the mine
+        let mut branch = solver.branch();
+        branch.assume_number(4, 1, 1).unwrap();
+        let hint = branch.hint().expect("The number decides the 50/50");
+        assert_eq!(hint.get_mine_fields(), &vec![(4, 0)]);
+
+        // The original doesn't know more than before
+        assert!(solver.hint().is_none());
+    }
+
+    #[test]
+    fn test_invalid_assumptions() {
+        let mut solver = create_solver(&fifty_fifty_field());
+        solver.solve().unwrap();
+        assert!(matches!(
+            solver.assume_mine(4, 0),
+            Err(SolverError::InvalidBoard(_))
+        ));
+
+        let mut branch = solver.branch();
+        assert!(matches!(
+            branch.assume_mine(0, 0),
+            Err(SolverError::InvalidBoard(_))
+        ));
+        assert!(matches!(
+            branch.assume_number(4, 0, 3),
+            Err(SolverError::InvalidBoard(_))
+        ));
+        branch.assume_mine(4, 0).unwrap();
+        // The field has a single mine
+        assert!(matches!(
+            branch.assume_mine(4, 1),
+            Err(SolverError::InvalidBoard(_))
+        ));
+        // The 1s next to it already border the flag
+        assert!(matches!(
+            branch.assume_number(4, 1, 0),
+            Err(SolverError::InconsistentFlags { .. })
+        ));
+    }
+}
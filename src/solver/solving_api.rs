@@ -1,146 +1,311 @@
+use super::budget::GiveUpReason;
 use super::cell_state::CellState;
+use super::config::SolverConfig;
+use super::error::SolverError;
 use super::findings::Finding;
-use super::strategy::SolvingStrategy;
+use super::incremental::IncrementalState;
+use super::snapshot::board_id;
+use super::stats::{SearchCounters, SolverStats};
+use super::view::SolverView;
 use crate::{Cell, MineSweeperField};
 use log::{debug, trace};
+use std::sync::Arc;
+use std::time::Instant;
 
 pub struct Solver {
-    pub(super) state: Vec<Vec<CellState>>,
+    /// Columns are shared with snapshots and branches until one of them changes, see `snapshot.rs`
+    pub(super) state: Vec<Arc<Vec<CellState>>>,
     pub(super) width: u32,
     pub(super) height: u32,
     pub(super) mines: u32,
     pub(super) start_cell: (u32, u32),
-    pub(super) solving_steps: Vec<Finding>,
+    pub(super) solving_steps: Vec<Arc<Finding>>,
+    pub(super) config: SolverConfig,
+    pub(super) incremental: IncrementalState,
+    pub(super) error: Option<SolverError>,
+    pub(super) gave_up: Option<GiveUpReason>,
+    pub(super) stats: SolverStats,
+    pub(super) search_counters: SearchCounters,
+    /// Set on branches, which treat every hidden cell as unknown
+    pub(super) blind: bool,
+    /// Identifies the board the solver started on, snapshots only restore onto the same board
+    pub(super) board_id: u64,
 }
 
-pub fn is_solvable(field: &impl MineSweeperField) -> bool {
-    let mut solver = create_solver(field);
-    solver.solve();
-    solver.is_solved()
+/// How a solving run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveOutcome {
+    /// Every safe cell is revealed
+    Solved,
+    /// No strategy could make progress, solving further requires a guess
+    Stuck,
+    /// The budget of the solver config ran out, the field may still be solvable
+    GaveUp(GiveUpReason),
+}
+
+pub fn is_solvable(field: &impl MineSweeperField, config: &SolverConfig) -> bool {
+    matches!(try_solve(field, config), Ok(SolveOutcome::Solved))
+}
+
+/// Solves `field` with a fresh solver, errors instead of panicking on malformed fields.
+pub fn try_solve(
+    field: &impl MineSweeperField,
+    config: &SolverConfig,
+) -> Result<SolveOutcome, SolverError> {
+    Solver::new(field, config).solve()
 }
 
 pub fn create_solver(field: &impl MineSweeperField) -> Solver {
-    Solver::new(field)
+    Solver::new(field, &SolverConfig::default())
 }
 
 impl Solver {
-    pub fn new(field: &impl MineSweeperField) -> Self {
+    pub fn new(field: &impl MineSweeperField, config: &SolverConfig) -> Self {
         let state = (0..field.get_width())
             .map(|x| {
                 (0..field.get_height())
-                    .map(|y| CellState::Hidden(field.get_cell(x, y).clone()))
+                    .map(|y| CellState::Hidden(Some(field.get_cell(x, y).clone())))
                     .collect()
             })
             .collect();
 
+        Solver::with_state(state, field.get_mines(), field.get_start_cell(), config)
+    }
+
+    /// A solver which hasn't made any step yet on the given board
+    pub(super) fn with_state(
+        state: Vec<Vec<CellState>>,
+        mines: u32,
+        start_cell: (u32, u32),
+        config: &SolverConfig,
+    ) -> Self {
+        let width = state.len() as u32;
+        let height = state.first().map_or(0, |column| column.len()) as u32;
+
+        let board_id = board_id(&state, mines, start_cell);
+
         Solver {
-            state,
-            width: field.get_width(),
-            height: field.get_height(),
-            mines: field.get_mines(),
-            start_cell: field.get_start_cell(),
+            state: state.into_iter().map(Arc::new).collect(),
+            width,
+            height,
+            mines,
+            start_cell,
             solving_steps: Vec::new(),
+            config: config.clone(),
+            incremental: IncrementalState::new(width, height, config.get_strategies().len()),
+            error: None,
+            gave_up: None,
+            stats: SolverStats::new(config.get_strategies().iter().map(|s| s.name().to_string())),
+            search_counters: SearchCounters::default(),
+            blind: false,
+            board_id,
         }
     }
 
     pub(crate) fn revealed_count(&self) -> u32 {
-        self.sorted_fields()
-            .filter(|&(x, y)| matches!(self.get_state(x, y), CellState::Revealed(_)))
-            .count() as u32
+        self.incremental.revealed
     }
 
-    pub(crate) fn get_state_grid(&self) -> &Vec<Vec<CellState>> {
-        &self.state
+    pub(crate) fn get_state_grid(&self) -> Vec<Vec<CellState>> {
+        self.state.iter().map(|column| column.to_vec()).collect()
     }
 
     pub fn is_solved(&self) -> bool {
-        self.sorted_fields()
-            .filter(|&(x, y)| !matches!(self.get_state(x, y), CellState::Revealed(_)))
-            .count() as u32
-            == self.mines
+        self.width * self.height - self.incremental.revealed == self.mines
     }
 
     pub fn get_solving_steps(&self) -> Vec<Finding> {
-        self.solving_steps.clone()
+        self.solving_steps
+            .iter()
+            .map(|finding| Finding::clone(finding))
+            .collect()
     }
 
-    pub fn solve(&mut self) {
+    /// Solves as far as the configured strategies get without guessing.
+    /// Errors if the start cell is invalid or a deduction contradicts the field.
+    pub fn solve(&mut self) -> Result<SolveOutcome, SolverError> {
         debug!("Starting solving process...");
         debug!(
             "Field dimensions: {}x{}, Mines: {}",
             self.width, self.height, self.mines
         );
         debug!("Start cell: {:?}", self.start_cell);
-        debug!("Opening start cell...");
-
-        self.open_start_cell();
-
-        trace!("{}", self.format_field_state());
 
         let mut step_count = 0;
-        while let Some(finding) = self.do_solving_step() {
-            self.solving_steps.push(finding);
+        while self.step().is_some() {
             step_count += 1;
 
             trace!("{}", self.format_field_state());
         }
 
+        if let Some(err) = &self.error {
+            return Err(err.clone());
+        }
+
         if self.is_solved() {
             debug!("Field solved in {} steps!", step_count);
+            Ok(SolveOutcome::Solved)
+        } else if let Some(reason) = self.gave_up {
+            debug!("Solver gave up after {} steps: {}", step_count, reason);
+            Ok(SolveOutcome::GaveUp(reason))
         } else {
             debug!(
                 "Solver could not solve the field after {} steps.",
                 step_count
             );
+            Ok(SolveOutcome::Stuck)
+        }
+    }
+
+    /// Makes a single solving step and returns its finding.
+    /// The first step opens the start cell, every further step applies the finding of the first strategy which makes progress.
+    /// Returns `None` once the solver is solved, stuck, out of budget or stopped by an error,
+    /// see [`Solver::get_error`] and [`Solver::get_give_up_reason`].
+    pub fn step(&mut self) -> Option<Finding> {
+        if self.error.is_some() || self.gave_up.is_some() {
+            return None;
+        }
+
+        match self.try_step() {
+            Ok(finding) => finding,
+            Err(err) => {
+                debug!("Solver stopped: {}", err);
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+
+    /// Iterator over the remaining solving steps, each item is the finding of one [`Solver::step`].
+    pub fn steps(&mut self) -> SolvingSteps<'_> {
+        SolvingSteps { solver: self }
+    }
+
+    /// The error which stopped the solver, if any
+    pub fn get_error(&self) -> Option<&SolverError> {
+        self.error.as_ref()
+    }
+
+    /// Why the solver stopped early, if its budget ran out.
+    /// A solver which gave up makes no further steps.
+    pub fn get_give_up_reason(&self) -> Option<GiveUpReason> {
+        self.gave_up
+    }
+
+    /// Read-only view of the visible board, e.g. to inspect it between steps
+    pub fn get_view(&self) -> SolverView<'_> {
+        SolverView::new(self)
+    }
+
+    fn try_step(&mut self) -> Result<Option<Finding>, SolverError> {
+        if !self.start_opened() {
+            debug!("Opening start cell...");
+            self.open_start_cell()?;
+            return Ok(self
+                .solving_steps
+                .last()
+                .map(|finding| Finding::clone(finding)));
         }
+        let finding = self.do_solving_step()?;
+        if let Some(finding) = &finding {
+            self.solving_steps.push(Arc::new(finding.clone()));
+        }
+        Ok(finding)
+    }
+
+    fn start_opened(&self) -> bool {
+        let (x, y) = self.start_cell;
+        x < self.width && y < self.height && matches!(self.get_state(x, y), CellState::Revealed(_))
     }
 
-    fn do_solving_step(&mut self) -> Option<Finding> {
+    fn do_solving_step(&mut self) -> Result<Option<Finding>, SolverError> {
         let mut step_solution: Option<Finding> = None;
+        let mut gave_up = None;
+
+        for index in 0..self.config.get_strategies().len() {
+            if let Err(reason) = self.config.get_budget().check() {
+                gave_up = Some(reason);
+                break;
+            }
+            let strategy = self.config.get_strategies()[index].clone();
 
-        for strategy in SolvingStrategy::iter() {
-            let finding: Finding = strategy.execute(&*self);
+            self.incremental.begin_strategy(index);
+            let start = Instant::now();
+            let finding: Finding = strategy.solve(&SolverView::new(self));
+
+            let stats = &mut self.stats.strategies[index];
+            stats.invocations += 1;
+            stats.time += start.elapsed();
+            stats.successes += finding.success() as usize;
 
             if finding.success() {
                 debug!(
-                    "Strategy {:?} made progress: Revealed {:?}, Flagged {:?}",
-                    strategy,
+                    "Strategy {} made progress: Revealed {:?}, Flagged {:?}",
+                    strategy.name(),
                     finding.get_safe_fields(),
                     finding.get_mine_fields()
                 );
 
+                let mut finding = finding;
+                finding.set_strategy(strategy.name());
                 step_solution = Some(finding);
                 break;
             }
+            // Later strategies may still make progress without the skipped search
+            gave_up = gave_up.or(finding.get_give_up_reason());
         }
 
+        self.incremental.end_strategies();
+
         if step_solution.is_none() {
-            debug!("No progress made in this step.");
-            return step_solution;
+            match gave_up {
+                Some(reason) => {
+                    debug!("Budget exhausted: {}", reason);
+                    self.gave_up = Some(reason);
+                }
+                None => debug!("No progress made in this step."),
+            }
+            return Ok(None);
         }
         let mut step_solution: Finding = step_solution.unwrap();
 
         let mut recursive_revealed_fields: Vec<Vec<(u32, u32)>> = Vec::new();
         for (x, y) in step_solution.get_safe_fields() {
-            self.reveal_cell(*x, *y, &mut recursive_revealed_fields, 0);
+            self.reveal_cell(*x, *y, &mut recursive_revealed_fields)?;
         }
         for (x, y) in step_solution.get_mine_fields() {
-            self.flag_cell(*x, *y);
+            self.flag_cell(*x, *y)?;
         }
 
+        self.stats.cascade_reveals += recursive_revealed_fields
+            .iter()
+            .map(Vec::len)
+            .sum::<usize>();
         step_solution.add_recursive_informations(recursive_revealed_fields);
-        Some(step_solution)
+        Ok(Some(step_solution))
     }
 
-    pub fn open_start_cell(&mut self) {
-        if self
-            .get_state(self.start_cell.0, self.start_cell.1)
-            .get_cell()
-            != &Cell::Empty
-        {
-            panic!(
-                "The Start Cell is directly bordering a Mine! The Solver expects the start cell to be empty."
-            );
+    pub fn open_start_cell(&mut self) -> Result<(), SolverError> {
+        let (x, y) = self.start_cell;
+        if x >= self.width || y >= self.height {
+            return Err(SolverError::InvalidStart {
+                x,
+                y,
+                reason: format!("outside of the {}x{} field", self.width, self.height),
+            });
+        }
+        match self.get_state(x, y) {
+            CellState::Hidden(_) if self.blind => return Err(SolverError::UnknownCell { x, y }),
+            CellState::Hidden(Some(Cell::Empty)) => {}
+            // Already opened, e.g. on a board built from visible information
+            CellState::Revealed(_) => return Ok(()),
+            _ => {
+                return Err(SolverError::InvalidStart {
+                    x,
+                    y,
+                    reason: "the solver expects the start cell to be empty".into(),
+                });
+            }
         }
 
         let mut finding = Finding::new();
@@ -148,14 +313,276 @@ impl Solver {
 
         let mut recursive_revealed_fields: Vec<Vec<(u32, u32)>> = Vec::new();
 
-        self.reveal_cell(
-            self.start_cell.0,
-            self.start_cell.1,
-            &mut recursive_revealed_fields,
-            0,
-        );
+        self.reveal_cell(x, y, &mut recursive_revealed_fields)?;
 
+        self.stats.cascade_reveals += recursive_revealed_fields
+            .iter()
+            .map(Vec::len)
+            .sum::<usize>();
         finding.add_recursive_informations(recursive_revealed_fields);
-        self.solving_steps.push(finding);
+        self.solving_steps.push(Arc::new(finding));
+        Ok(())
+    }
+}
+
+/// Iterator over the solving steps of a [`Solver`], see [`Solver::steps`].
+pub struct SolvingSteps<'a> {
+    solver: &'a mut Solver,
+}
+
+impl Iterator for SolvingSteps<'_> {
+    type Item = Finding;
+
+    fn next(&mut self) -> Option<Finding> {
+        self.solver.step()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, Mines, SolvingStrategy, Strategy};
+
+    fn fifty_fifty_field() -> DefinedField {
+        let mut field = DefinedField::new(5, 2, Mines::Count(1)).unwrap();
+        field.initialize(vec![(4, 0)]);
+        field.set_start_cell(0, 0);
+        field
+    }
+
+    /// Claims the same cells every step, right or wrong
+    struct Claim {
+        safe: Vec<(u32, u32)>,
+        mines: Vec<(u32, u32)>,
+    }
+
+    impl Strategy for Claim {
+        fn name(&self) -> &str {
+            "Claim"
+        }
+
+        fn solve(&self, _view: &SolverView) -> Finding {
+            let mut finding = Finding::new();
+            finding.add_safe_fields(self.safe.clone());
+            finding.add_mine_fields(self.mines.clone());
+            finding
+        }
+    }
+
+    fn solve_with_claim(
+        safe: Vec<(u32, u32)>,
+        mines: Vec<(u32, u32)>,
+    ) -> Result<SolveOutcome, SolverError> {
+        let config = SolverConfig::new(vec![]).with_strategy(Claim { safe, mines });
+        try_solve(&fifty_fifty_field(), &config)
+    }
+
+    #[test]
+    fn test_outcomes() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        assert_eq!(
+            try_solve(&field, &SolverConfig::default()),
+            Ok(SolveOutcome::Solved)
+        );
+        assert_eq!(
+            try_solve(&fifty_fifty_field(), &SolverConfig::default()),
+            Ok(SolveOutcome::Stuck)
+        );
+    }
+
+    #[test]
+    fn test_invalid_start() {
+        let mut field = fifty_fifty_field();
+        field.set_start_cell(3, 0);
+
+        let result = try_solve(&field, &SolverConfig::default());
+
+        assert!(matches!(
+            result,
+            Err(SolverError::InvalidStart { x: 3, y: 0, .. })
+        ));
+        assert!(!is_solvable(&field, &SolverConfig::default()));
+    }
+
+    #[test]
+    fn test_revealing_a_mine_is_a_contradiction() {
+        assert_eq!(
+            solve_with_claim(vec![(4, 0)], vec![]),
+            Err(SolverError::Contradiction { x: 4, y: 0 })
+        );
+    }
+
+    #[test]
+    fn test_flagging_a_safe_cell_is_a_contradiction() {
+        assert_eq!(
+            solve_with_claim(vec![], vec![(4, 1)]),
+            Err(SolverError::Contradiction { x: 4, y: 1 })
+        );
+    }
+
+    #[test]
+    fn test_too_many_flags() {
+        let mut solver = Solver::new(
+            &fifty_fifty_field(),
+            &SolverConfig::new(vec![]).with_strategy(Claim {
+                safe: vec![],
+                mines: vec![(4, 0), (4, 1)],
+            }),
+        );
+
+        assert_eq!(
+            solver.solve(),
+            Err(SolverError::InconsistentFlags {
+                x: 3,
+                y: 0,
+                number: 1,
+                flags: 2
+            })
+        );
+        // The offending flag is never placed
+        assert_eq!(solver.get_remaining_mines(), 0);
+        assert!(matches!(solver.get_state(4, 1), CellState::Hidden(_)));
+    }
+
+    #[test]
+    fn test_steps_match_solve() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        let mut solver = create_solver(&field);
+        solver.solve().unwrap();
+
+        let mut stepping = create_solver(&field);
+        let start = stepping
+            .step()
+            .expect("The first step opens the start cell");
+        assert_eq!(start.get_safe_fields(), &vec![field.get_start_cell()]);
+        assert_eq!(start.get_strategy(), None);
+
+        let rest: Vec<Finding> = stepping.steps().collect();
+        assert!(rest.iter().all(|finding| finding.get_strategy().is_some()));
+        assert!(stepping.is_solved());
+        assert!(stepping.step().is_none());
+
+        let expected = solver.get_solving_steps();
+        assert_eq!(rest.len() + 1, expected.len());
+        for (found, expected) in rest.iter().zip(&expected[1..]) {
+            assert_eq!(found.get_safe_fields(), expected.get_safe_fields());
+            assert_eq!(found.get_mine_fields(), expected.get_mine_fields());
+        }
+    }
+
+    #[test]
+    fn test_stop_at_first_sat_step() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        // Without box logic and linear algebra the hard field needs SAT before its last step
+        let config = SolverConfig::new(
+            SolvingStrategy::iter()
+                .filter(|&s| s != SolvingStrategy::BoxLogic && s != SolvingStrategy::LinearAlgebra)
+                .collect(),
+        );
+        let mut solver = Solver::new(&field, &config);
+        let mut revealed = solver.revealed_count();
+        let sat_step = solver
+            .steps()
+            .inspect(|finding| assert!(finding.success()))
+            .find(|finding| finding.get_strategy() == Some("Sat"))
+            .expect("The hard field needs the SAT strategy");
+
+        // The board can be inspected between steps
+        let view = solver.get_view();
+        for &(x, y) in sat_step.get_safe_fields() {
+            assert!(view.is_revealed(x, y));
+        }
+        for &(x, y) in sat_step.get_mine_fields() {
+            assert!(view.is_flagged(x, y));
+        }
+        assert!(solver.revealed_count() > revealed);
+        revealed = solver.revealed_count();
+
+        assert!(solver.step().is_some());
+        assert!(solver.revealed_count() >= revealed);
+    }
+
+    #[test]
+    fn test_step_stops_on_error() {
+        let config = SolverConfig::new(vec![]).with_strategy(Claim {
+            safe: vec![(4, 0)],
+            mines: vec![],
+        });
+        let mut solver = Solver::new(&fifty_fifty_field(), &config);
+
+        assert!(solver.step().is_some());
+        assert!(solver.step().is_none());
+        assert_eq!(
+            solver.get_error(),
+            Some(&SolverError::Contradiction { x: 4, y: 0 })
+        );
+        assert!(solver.step().is_none());
+        assert_eq!(
+            solver.solve(),
+            Err(SolverError::Contradiction { x: 4, y: 0 })
+        );
+    }
+
+    #[test]
+    fn test_huge_opening() {
+        // 2000x1000 cells with isolated mines, the start cell opens the whole field in one cascade
+        let (width, height) = (2000, 1000);
+        let mines: Vec<(u32, u32)> = (0..width / 40)
+            .flat_map(|x| (0..height / 40).map(move |y| (x * 40 + 20, y * 40 + 20)))
+            .collect();
+        let mut field = DefinedField::new(width, height, Mines::Count(mines.len() as u32)).unwrap();
+        field.initialize(mines.clone());
+        field.set_start_cell(0, 0);
+
+        let mut solver = create_solver(&field);
+        assert_eq!(solver.solve(), Ok(SolveOutcome::Solved));
+
+        let start = &solver.get_solving_steps()[0];
+        let layers = start.get_recursive_revelations();
+        let revealed: usize = layers.iter().map(Vec::len).sum();
+        assert_eq!(revealed + 1, (width * height) as usize - mines.len());
+        // Layers are the distance from the start cell, the farthest corner is 1999 cells away
+        assert_eq!(layers.len(), 1999);
+        assert_eq!(layers[0].len(), 3);
+    }
+
+    #[test]
+    fn test_budget_gives_up() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        // The hard field needs the SAT search, which can't finish with a single node
+        let limited = SolverConfig::default()
+            .with_budget(crate::SolverBudget::new().with_max_search_nodes(1));
+        let mut solver = Solver::new(&field, &limited);
+        assert_eq!(
+            solver.solve(),
+            Ok(SolveOutcome::GaveUp(GiveUpReason::NodeLimit))
+        );
+        assert_eq!(solver.get_give_up_reason(), Some(GiveUpReason::NodeLimit));
+        assert!(solver.step().is_none());
+
+        let token = crate::CancellationToken::new();
+        let cancellable = SolverConfig::default()
+            .with_budget(crate::SolverBudget::new().with_cancellation(token.clone()));
+        let mut solver = Solver::new(&field, &cancellable);
+        assert!(solver.step().is_some());
+        token.cancel();
+        assert_eq!(
+            solver.solve(),
+            Ok(SolveOutcome::GaveUp(GiveUpReason::Cancelled))
+        );
+        assert_eq!(solver.get_solving_steps().len(), 1);
+
+        assert_eq!(
+            try_solve(&field, &SolverConfig::default()),
+            Ok(SolveOutcome::Solved)
+        );
     }
 }
@@ -1,123 +1,132 @@
-use super::{CellState, Solver};
+use super::{CellState, Solver, SolverError};
 use crate::Cell;
 use log::debug;
 
 impl Solver {
     pub(super) fn get_remaining_mines(&self) -> u32 {
-        let mut flagged_count = 0;
-        for (x, y) in self.sorted_fields() {
-            if matches!(self.get_state(x, y), CellState::Flagged(_)) {
-                flagged_count += 1;
-            }
-        }
-        self.mines.saturating_sub(flagged_count)
+        self.mines.saturating_sub(self.incremental.flagged)
     }
 
     pub(super) fn get_state(&self, x: u32, y: u32) -> &CellState {
         &self.state[x as usize][y as usize]
     }
 
-    pub(super) fn flag_cell(&mut self, x: u32, y: u32) {
-        let state = self.get_state(x, y);
+    /// Flags a hidden cell. The board stays unchanged if the flag contradicts a revealed number or the field.
+    pub(super) fn flag_cell(&mut self, x: u32, y: u32) -> Result<(), SolverError> {
+        let CellState::Hidden(truth) = self.get_state(x, y) else {
+            return Ok(());
+        };
+        let truth = if self.blind { None } else { truth.clone() };
+
+        for (nx, ny) in self.surrounding_fields(x, y, None) {
+            if let CellState::Revealed(Cell::Number(number)) = self.get_state(nx, ny) {
+                let flags = self.get_surrounding_flag_count(nx, ny) + 1;
+                if flags > *number {
+                    return Err(SolverError::InconsistentFlags {
+                        x: nx,
+                        y: ny,
+                        number: *number,
+                        flags,
+                    });
+                }
+            }
+        }
 
-        if !matches!(state, CellState::Hidden(_)) {
-            return;
+        if truth.is_some_and(|cell| cell != Cell::Mine) {
+            debug!("{}", self.format_field_state());
+            debug!("Flagged a safe cell at ({}, {})! Solver failed.", x, y);
+            return Err(SolverError::Contradiction { x, y });
         }
 
-        // Don't bother checking if it's actually a mine here, if its no mine, were definitely hitting one in the next step
-        let cell = state.get_cell().clone();
-        self.state[x as usize][y as usize] = CellState::Flagged(cell);
+        self.set_state(x, y, CellState::Flagged(Cell::Mine));
+        Ok(())
     }
 
-    #[track_caller]
+    /// Reveals a cell and everything it opens. Empty cells and numbers whose mines are all flagged
+    /// open their surrounding, which is worked off breadth first with an explicit worklist,
+    /// so even openings of millions of cells don't grow the stack.
+    /// `recursive_revealed_fields[i]` collects the cells opened `i + 1` layers away from the cell.
     pub(super) fn reveal_cell(
         &mut self,
         x: u32,
         y: u32,
         recursive_revealed_fields: &mut Vec<Vec<(u32, u32)>>,
-        depth: usize,
-    ) {
-        let state = self.get_state(x, y);
+    ) -> Result<(), SolverError> {
+        let mut layer = Vec::new();
+        if self.open_cell(x, y)? {
+            layer.push((x, y));
+        }
 
-        if let CellState::Revealed(_) = state {
-            return;
+        let mut depth = 0;
+        while !layer.is_empty() {
+            let mut next_layer = Vec::new();
+            for (x, y) in layer {
+                for (sx, sy) in self.surrounding_fields(x, y, None) {
+                    if let CellState::Hidden(_) = self.get_state(sx, sy) {
+                        // Ensure an vector exists for this depth
+                        while recursive_revealed_fields.len() <= depth {
+                            recursive_revealed_fields.push(vec![]);
+                        }
+                        recursive_revealed_fields[depth].push((sx, sy));
+
+                        if self.open_cell(sx, sy)? {
+                            next_layer.push((sx, sy));
+                        }
+                    }
+                }
+            }
+            layer = next_layer;
+            depth += 1;
         }
-        let cell = state.get_cell().clone();
 
-        match cell {
+        Ok(())
+    }
+
+    /// Reveals a single cell, returns whether its surrounding opens as well
+    fn open_cell(&mut self, x: u32, y: u32) -> Result<bool, SolverError> {
+        let cell = match self.get_state(x, y) {
+            CellState::Revealed(_) => return Ok(false),
+            CellState::Hidden(_) if self.blind => return Err(SolverError::UnknownCell { x, y }),
+            CellState::Hidden(Some(cell)) | CellState::Flagged(cell) => cell.clone(),
+            CellState::Hidden(None) => return Err(SolverError::UnknownCell { x, y }),
+        };
+
+        let number = match cell {
             Cell::Mine => {
                 debug!("{}", self.format_field_state());
                 debug!("Stepped on a mine at ({}, {})! Solver failed.", x, y);
-                panic!("Solver hit a mine!");
-            }
-            Cell::Number(n) => {
-                self.state[x as usize][y as usize] = CellState::Revealed(cell);
-
-                if self.get_surrounding_flag_count(x, y) == n {
-                    self.reveal_surrounding_cells(x, y, recursive_revealed_fields, depth);
-                }
+                return Err(SolverError::Contradiction { x, y });
             }
-            Cell::Empty => {
-                self.state[x as usize][y as usize] = CellState::Revealed(cell);
+            Cell::Number(n) => n,
+            Cell::Empty => 0,
+        };
 
-                self.reveal_surrounding_cells(x, y, recursive_revealed_fields, depth);
-            }
+        let flags = self.get_surrounding_flag_count(x, y);
+        if flags > number {
+            return Err(SolverError::InconsistentFlags {
+                x,
+                y,
+                number,
+                flags,
+            });
         }
-    }
 
-    #[track_caller]
-    pub(super) fn reveal_surrounding_cells(
-        &mut self,
-        x: u32,
-        y: u32,
-        recursive_revealed_fields: &mut Vec<Vec<(u32, u32)>>,
-        depth: usize,
-    ) {
-        // Ensure an vector exists for this depth
-        while recursive_revealed_fields.len() <= depth {
-            recursive_revealed_fields.push(vec![]);
-        }
+        self.set_state(x, y, CellState::Revealed(cell));
 
-        for (sx, sy) in self.surrounding_fields(x, y, None) {
-            if let CellState::Hidden(_) = self.get_state(sx, sy) {
-                recursive_revealed_fields[depth].push((sx, sy));
-                self.reveal_cell(sx, sy, recursive_revealed_fields, depth + 1);
-            }
-        }
+        // Numbers whose mines are all flagged open their surrounding like empty cells
+        Ok(flags == number)
     }
 
     pub(super) fn has_unrevealed_neighbours(&self, x: u32, y: u32) -> bool {
-        for (new_x, new_y) in self.surrounding_fields(x, y, None) {
-            if let CellState::Hidden(_) = self.get_state(new_x, new_y) {
-                return true;
-            }
-        }
-
-        false
+        self.incremental.hidden_neighbours(x, y) > 0
     }
 
     pub(super) fn get_surrounding_flag_count(&self, x: u32, y: u32) -> u8 {
-        let mut flag_count = 0;
-
-        for (sx, sy) in self.surrounding_fields(x, y, None) {
-            if let CellState::Flagged(_) = self.get_state(sx, sy) {
-                flag_count += 1;
-            }
-        }
-
-        flag_count
+        self.incremental.flagged_neighbours(x, y)
     }
 
     pub(super) fn get_surrounding_unrevealed_count(&self, x: u32, y: u32) -> u8 {
-        let mut count = 0;
-
-        for (new_x, new_y) in self.surrounding_fields(x, y, None) {
-            if let CellState::Hidden(_) = self.get_state(new_x, new_y) {
-                count += 1;
-            }
-        }
-
-        count
+        self.incremental.hidden_neighbours(x, y)
     }
 
     pub(super) fn get_surrounding_unrevealed(&self, x: u32, y: u32) -> Vec<(u32, u32)> {
@@ -134,27 +143,20 @@ impl Solver {
 
     pub(super) fn get_reduced_count(&self, x: u32, y: u32) -> u8 {
         let flag_count = self.get_surrounding_flag_count(x, y);
-        let number = match self.get_state(x, y).get_cell() {
-            Cell::Number(n) => n,
+        let number = match self.get_state(x, y) {
+            CellState::Revealed(Cell::Number(n)) => n,
             _ => panic!(
                 "get_reduced_count called on non-number cell at ({}, {})",
                 x, y
             ),
         };
 
-        if flag_count > *number {
-            panic!(
-                "Flag count is greater than number at ({}, {}) Flagcount: {}\t Number: {}",
-                x, y, flag_count, number
-            );
-        }
-
-        number - flag_count
+        // flag_cell and reveal_cell never let the flags exceed a revealed number
+        number.saturating_sub(flag_count)
     }
 
     pub(super) fn has_informations(&self, x: u32, y: u32) -> bool {
-        matches!(self.get_state(x, y), CellState::Revealed(_))
-            && matches!(self.get_state(x, y).get_cell(), Cell::Number(_))
+        matches!(self.get_state(x, y), CellState::Revealed(Cell::Number(_)))
             && self.has_unrevealed_neighbours(x, y)
     }
 }
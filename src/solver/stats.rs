@@ -0,0 +1,183 @@
+use super::Solver;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/*
+Solver statistics:
+
+The solver measures every strategy call in do_solving_step (calls, successful calls, time).
+Strategies only get a shared reference and the SAT strategy searches its components in parallel,
+so searches report their effort through atomic counters instead:
+- components: every component search of the SAT and GlobalCount strategies, also those which gave up
+- search nodes: subproblems the search explored, including those answered from its cache
+- propagations: cells forced by propagation during the searches
+Cascade reveals are the cells opened recursively by empty cells or numbers whose mines are all flagged.
+*/
+
+/// Calls and time of one configured strategy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategyStats {
+    pub name: String,
+    pub invocations: usize,
+    /// Invocations which made progress
+    pub successes: usize,
+    pub time: Duration,
+}
+
+/// Where a solver spent its work, see [`Solver::get_stats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolverStats {
+    /// In the order of the solver config
+    pub strategies: Vec<StrategyStats>,
+    /// Components searched by SAT based strategies
+    pub sat_components: usize,
+    pub largest_component: usize,
+    /// Cells forced by propagation during the searches
+    pub propagations: usize,
+    /// Backtracking nodes explored by the searches
+    pub search_nodes: usize,
+    /// Cells revealed recursively after a step
+    pub cascade_reveals: usize,
+}
+
+impl SolverStats {
+    pub(super) fn new(strategy_names: impl Iterator<Item = String>) -> Self {
+        SolverStats {
+            strategies: strategy_names
+                .map(|name| StrategyStats {
+                    name,
+                    invocations: 0,
+                    successes: 0,
+                    time: Duration::ZERO,
+                })
+                .collect(),
+            sat_components: 0,
+            largest_component: 0,
+            propagations: 0,
+            search_nodes: 0,
+            cascade_reveals: 0,
+        }
+    }
+
+    /// Time spent in all strategies
+    pub fn total_time(&self) -> Duration {
+        self.strategies.iter().map(|strategy| strategy.time).sum()
+    }
+}
+
+impl fmt::Display for SolverStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<12} {:>8} {:>9} {:>12}",
+            "Strategy", "Calls", "Successes", "Time"
+        )?;
+        for strategy in &self.strategies {
+            writeln!(
+                f,
+                "{:<12} {:>8} {:>9} {:>12}",
+                strategy.name,
+                strategy.invocations,
+                strategy.successes,
+                format!("{:.2?}", strategy.time)
+            )?;
+        }
+        writeln!(
+            f,
+            "SAT: {} components (largest {}), {} search nodes, {} propagations",
+            self.sat_components, self.largest_component, self.search_nodes, self.propagations
+        )?;
+        write!(
+            f,
+            "Cascade reveals: {}, total time {:.2?}",
+            self.cascade_reveals,
+            self.total_time()
+        )
+    }
+}
+
+/// Effort of the component searches, shared between the threads searching them
+#[derive(Debug, Default)]
+pub(crate) struct SearchCounters {
+    components: AtomicUsize,
+    largest_component: AtomicUsize,
+    search_nodes: AtomicUsize,
+    propagations: AtomicUsize,
+}
+
+impl SearchCounters {
+    pub(crate) fn record(&self, size: usize, search_nodes: usize, propagations: usize) {
+        self.components.fetch_add(1, Ordering::Relaxed);
+        self.largest_component.fetch_max(size, Ordering::Relaxed);
+        self.search_nodes.fetch_add(search_nodes, Ordering::Relaxed);
+        self.propagations.fetch_add(propagations, Ordering::Relaxed);
+    }
+}
+
+impl Solver {
+    /// Statistics of all steps made so far
+    pub fn get_stats(&self) -> SolverStats {
+        let counters = &self.search_counters;
+        SolverStats {
+            sat_components: counters.components.load(Ordering::Relaxed),
+            largest_component: counters.largest_component.load(Ordering::Relaxed),
+            search_nodes: counters.search_nodes.load(Ordering::Relaxed),
+            propagations: counters.propagations.load(Ordering::Relaxed),
+            ..self.stats.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DefinedField, SolverConfig, SolvingStrategy, create_solver};
+
+    #[test]
+    fn test_stats_of_hard_field() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+        let mut solver = create_solver(&field);
+        solver.solve().unwrap();
+        let stats = solver.get_stats();
+
+        let names: Vec<&str> = stats.strategies.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names[0], "Simple");
+        assert_eq!(
+            stats.strategies.len(),
+            SolverConfig::default().get_strategies().len()
+        );
+
+        // Every step but the start was made by exactly one strategy
+        let successes: usize = stats.strategies.iter().map(|s| s.successes).sum();
+        assert_eq!(successes + 1, solver.get_solving_steps().len());
+        for strategy in &stats.strategies {
+            assert!(strategy.successes <= strategy.invocations);
+        }
+        // Every step starts with the first strategy
+        assert!(stats.strategies[0].invocations >= successes);
+
+        assert!(stats.sat_components > 0);
+        assert!(stats.largest_component > 0);
+        assert!(stats.search_nodes >= stats.sat_components);
+        assert!(stats.cascade_reveals > 0);
+        assert!(stats.to_string().contains("Reduction"));
+    }
+
+    #[test]
+    fn test_no_search_without_sat() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+        let config = SolverConfig::new(vec![SolvingStrategy::Simple]);
+        let mut solver = crate::Solver::new(&field, &config);
+        solver.solve().unwrap();
+        let stats = solver.get_stats();
+
+        assert_eq!(stats.sat_components, 0);
+        assert_eq!(stats.search_nodes, 0);
+        assert_eq!(
+            stats.strategies[0].successes + 1,
+            solver.get_solving_steps().len()
+        );
+    }
+}
@@ -0,0 +1,391 @@
+use super::sat_solver::find_independent_components;
+use super::{Explanation, Finding, Rule, Solver};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/*
+Box logic strategy:
+
+Generalizes the reduction strategy from pairs of neighbouring numbers to sets of numbers.
+A region is a set of hidden cells with bounds on its mines, starting with one region per number
+(its hidden neighbours, exactly its reduced count). Two overlapping regions A and B give bounds for
+- the shared cells:   at most min(A.max, B.max, size), at least A.min - |A only| and B.min - |B only|
+- the cells of A only: at least A.min - shared.max, at most A.max - shared.min (likewise for B only)
+A subset is the case where one side is empty, a disjoint union inside a larger region follows
+from subtracting its parts one after another.
+Every new region and every tightened bound is combined again with the regions of the numbers it overlaps,
+until nothing changes. Regions whose bounds allow anything from no mine to all cells are dropped.
+Each region remembers the numbers it was derived from, regions of more than MAX_CONSTRAINTS numbers
+are dropped as well, so the search stays polynomial and the deductions stay those a player can follow.
+A region without room for a mine is safe, a region which needs all its cells is mines.
+*/
+
+/// Most numbers a region may be derived from
+const MAX_CONSTRAINTS: usize = 4;
+/// Most regions tracked per component, a safeguard against huge components
+const MAX_REGIONS: usize = 5000;
+
+pub fn solve(solver: &Solver) -> Finding {
+    let mut finding = Finding::new();
+
+    let dirty: HashSet<(u32, u32)> = solver.dirty_frontier(1).into_iter().collect();
+    if dirty.is_empty() {
+        return finding;
+    }
+
+    for mut component in find_independent_components(solver) {
+        // Sorted, so the capped search and the explanations don't depend on the component order
+        component.sort_unstable_by_key(|&(x, y)| (y, x));
+        let columns: HashMap<(u32, u32), usize> = component
+            .iter()
+            .enumerate()
+            .map(|(i, &field)| (field, i))
+            .collect();
+
+        let numbers: Vec<(u32, u32)> = solver
+            .frontier()
+            .filter(|&(x, y)| {
+                solver
+                    .get_surrounding_unrevealed(x, y)
+                    .iter()
+                    .any(|field| columns.contains_key(field))
+            })
+            .collect();
+        if !numbers.iter().any(|number| dirty.contains(number)) {
+            continue;
+        }
+
+        let mut regions = Regions::default();
+        for (i, &(x, y)) in numbers.iter().enumerate() {
+            let mut cells: Vec<usize> = solver
+                .get_surrounding_unrevealed(x, y)
+                .iter()
+                .map(|field| columns[field])
+                .collect();
+            cells.sort_unstable();
+            let count = solver.get_reduced_count(x, y) as usize;
+            regions.add_number(cells, count, i);
+        }
+        regions.combine_all();
+
+        // Smaller sets of numbers first, they are the easier explanation
+        let mut decided: Vec<&Region> = regions
+            .regions
+            .iter()
+            .filter(|r| r.min <= r.max && (r.max == 0 || r.min == r.cells.len()))
+            .collect();
+        decided.sort_by_key(|r| r.sources.len());
+
+        let mut known: HashSet<usize> = HashSet::new();
+        for region in decided {
+            let cells: Vec<(u32, u32)> = region
+                .cells
+                .iter()
+                .filter(|&&cell| known.insert(cell))
+                .map(|&cell| component[cell])
+                .collect();
+            if cells.is_empty() {
+                continue;
+            }
+
+            let (mines, safe) = if region.max == 0 {
+                (vec![], cells)
+            } else {
+                (cells, vec![])
+            };
+            let constraints = region
+                .sources
+                .iter()
+                .map(|&i| solver.constraint_cell(numbers[i].0, numbers[i].1))
+                .collect();
+
+            finding.add_explanation(Explanation::new(
+                Rule::BoxLogic,
+                constraints,
+                mines.clone(),
+                safe.clone(),
+            ));
+            finding.add_mine_fields(mines);
+            finding.add_safe_fields(safe);
+        }
+    }
+
+    finding
+}
+
+/// Hidden cells (indices into the component, sorted) with bounds on their mines
+#[derive(Debug, Clone)]
+struct Region {
+    cells: Vec<usize>,
+    min: usize,
+    max: usize,
+    /// Indices of the numbers the bounds were derived from, sorted
+    sources: Vec<usize>,
+}
+
+#[derive(Default)]
+struct Regions {
+    regions: Vec<Region>,
+    by_cells: HashMap<Vec<usize>, usize>,
+    /// Regions of the numbers containing each cell
+    numbers_by_cell: HashMap<usize, Vec<usize>>,
+    queue: VecDeque<usize>,
+}
+
+impl Regions {
+    /// Adds the region of the number at `index`, the number's hidden neighbours
+    fn add_number(&mut self, cells: Vec<usize>, count: usize, index: usize) {
+        self.add(cells.clone(), count, count, vec![index]);
+        // Numbers with the same hidden neighbours share one region
+        let Some(&region) = self.by_cells.get(&cells) else {
+            return;
+        };
+        for cell in cells {
+            let regions = self.numbers_by_cell.entry(cell).or_default();
+            if !regions.contains(&region) {
+                regions.push(region);
+            }
+        }
+    }
+
+    /// Adds a region or tightens the bounds of a known one, queueing it if anything changed
+    fn add(&mut self, cells: Vec<usize>, min: usize, max: usize, sources: Vec<usize>) {
+        let max = max.min(cells.len());
+        if cells.is_empty() || sources.len() > MAX_CONSTRAINTS || (min == 0 && max == cells.len()) {
+            return;
+        }
+
+        if let Some(&index) = self.by_cells.get(&cells) {
+            let region = &mut self.regions[index];
+            if min <= region.min && max >= region.max {
+                return;
+            }
+            let sources = merge(&region.sources, &sources);
+            if sources.len() > MAX_CONSTRAINTS {
+                return;
+            }
+            region.min = region.min.max(min);
+            region.max = region.max.min(max);
+            region.sources = sources;
+            self.queue.push_back(index);
+            return;
+        }
+
+        if self.regions.len() >= MAX_REGIONS {
+            return;
+        }
+        let index = self.regions.len();
+        self.by_cells.insert(cells.clone(), index);
+        self.regions.push(Region {
+            cells,
+            min,
+            max,
+            sources,
+        });
+        self.queue.push_back(index);
+    }
+
+    /// Combines every queued region with the regions of all numbers it overlaps, until no bound changes
+    fn combine_all(&mut self) {
+        while let Some(index) = self.queue.pop_front() {
+            let region = self.regions[index].clone();
+            let mut numbers: Vec<usize> = region
+                .cells
+                .iter()
+                .filter_map(|cell| self.numbers_by_cell.get(cell))
+                .flatten()
+                .copied()
+                .filter(|&number| number != index)
+                .collect();
+            numbers.sort_unstable();
+            numbers.dedup();
+
+            for number in numbers {
+                let other = self.regions[number].clone();
+                if region.min > region.max || other.min > other.max {
+                    // A contradicting board, nothing follows from it
+                    continue;
+                }
+                self.combine(&region, &other);
+            }
+        }
+    }
+
+    fn combine(&mut self, a: &Region, b: &Region) {
+        let sources = merge(&a.sources, &b.sources);
+        if sources.len() > MAX_CONSTRAINTS {
+            return;
+        }
+
+        let shared: Vec<usize> = a
+            .cells
+            .iter()
+            .filter(|cell| b.cells.binary_search(cell).is_ok())
+            .copied()
+            .collect();
+        let a_only: Vec<usize> = a
+            .cells
+            .iter()
+            .filter(|cell| shared.binary_search(cell).is_err())
+            .copied()
+            .collect();
+        let b_only: Vec<usize> = b
+            .cells
+            .iter()
+            .filter(|cell| shared.binary_search(cell).is_err())
+            .copied()
+            .collect();
+
+        let shared_max = a.max.min(b.max).min(shared.len());
+        let shared_min = a
+            .min
+            .saturating_sub(a_only.len())
+            .max(b.min.saturating_sub(b_only.len()));
+
+        for (only, region) in [(a_only, a), (b_only, b)] {
+            let min = region.min.saturating_sub(shared_max);
+            let max = region.max.saturating_sub(shared_min);
+            self.add(only, min, max, sources.clone());
+        }
+        self.add(shared, shared_min, shared_max, sources);
+    }
+}
+
+/// Union of two sorted index lists
+fn merge(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut merged: Vec<usize> = a.iter().chain(b).copied().collect();
+    merged.sort_unstable();
+    merged.dedup();
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::reference::reference_deductions;
+    use crate::solver::{VisibleCell, strategy::linear_algebra};
+    use crate::{SolverConfig, SolvingStrategy};
+
+    fn cheap_config() -> SolverConfig {
+        SolverConfig::new(vec![
+            SolvingStrategy::Simple,
+            SolvingStrategy::Reduction,
+            SolvingStrategy::Patterns,
+        ])
+    }
+
+    fn assert_proven(solver: &Solver, finding: &Finding) {
+        let reference = reference_deductions(solver);
+        for field in finding.get_mine_fields() {
+            assert!(reference.mines.contains(field));
+        }
+        for field in finding.get_safe_fields() {
+            assert!(reference.safe.contains(field));
+        }
+    }
+
+    /// Rows of a visible board: '?' hidden, 'F' flagged, '.' empty or the number
+    fn board(rows: &[&str]) -> Vec<Vec<VisibleCell>> {
+        let rows: Vec<Vec<char>> = rows
+            .iter()
+            .map(|row| row.replace(' ', "").chars().collect())
+            .collect();
+        (0..rows[0].len())
+            .map(|x| {
+                rows.iter()
+                    .map(|row| match row[x] {
+                        '?' => VisibleCell::Hidden,
+                        'F' => VisibleCell::Flagged,
+                        '.' => VisibleCell::Revealed(0),
+                        n => VisibleCell::Revealed(n.to_digit(10).unwrap() as u8),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_combine() {
+        // {0, 1, 2} has 2 mines, {1, 2, 3} has 1: the shared cells hold at most 1,
+        // so 0 is a mine and 3 is safe
+        let mut regions = Regions::default();
+        regions.add_number(vec![0, 1, 2], 2, 0);
+        regions.add_number(vec![1, 2, 3], 1, 1);
+        regions.combine_all();
+
+        let bounds = |cells: Vec<usize>| {
+            let region = &regions.regions[regions.by_cells[&cells]];
+            (region.min, region.max, region.sources.clone())
+        };
+        assert_eq!(bounds(vec![0]), (1, 1, vec![0, 1]));
+        assert_eq!(bounds(vec![3]), (0, 0, vec![0, 1]));
+        assert_eq!(bounds(vec![1, 2]), (1, 1, vec![0, 1]));
+    }
+
+    #[test]
+    fn test_four_numbers() {
+        let cells = board(&[
+            "? ? ? ? ? ? ? ?",
+            "? ? ? ? ? ? ? ?",
+            "? 2 3 3 ? ? ? ?",
+            "? ? 2 ? ? ? ? ?",
+            "1 1 2 F ? ? ? ?",
+            ". . 1 3 F ? ? ?",
+            ". . . 2 ? ? ? ?",
+            ". . . 1 ? ? ? ?",
+        ]);
+        assert!(
+            Solver::from_visible(&cells, 12, &cheap_config())
+                .unwrap()
+                .hint()
+                .is_none()
+        );
+
+        let solver = Solver::from_visible(&cells, 12, &SolverConfig::default()).unwrap();
+        eprintln!("LA: {:?}", super::super::linear_algebra::solve(&solver));
+        let finding = solve(&solver);
+        assert_proven(&solver, &finding);
+        assert!(finding.get_mine_fields().contains(&(3, 1)));
+        assert!(finding.get_safe_fields().contains(&(0, 1)));
+        assert!(finding.get_safe_fields().contains(&(0, 2)));
+
+        // The explanation names the four numbers needed, not the whole region
+        let explanation = finding
+            .get_explanations()
+            .iter()
+            .find(|e| e.mine_fields.contains(&(3, 1)))
+            .unwrap();
+        let positions: Vec<(u32, u32)> =
+            explanation.constraints.iter().map(|c| c.position).collect();
+        assert_eq!(positions, vec![(1, 2), (2, 2), (2, 3), (0, 4)]);
+        assert_eq!(explanation.constraints[2].reduced_count, 1);
+    }
+
+    #[test]
+    fn test_beyond_linear_algebra() {
+        let cells = board(&[
+            "? ? ? ? ? ? ? ? ?",
+            "? ? ? ? ? 2 2 1 ?",
+            "? 2 2 3 ? ? 1 ? ?",
+        ]);
+        assert!(
+            Solver::from_visible(&cells, 6, &cheap_config())
+                .unwrap()
+                .hint()
+                .is_none()
+        );
+
+        // The bounds of the regions prove more than any row of the eliminated equations
+        let solver = Solver::from_visible(&cells, 6, &SolverConfig::default()).unwrap();
+        assert!(!linear_algebra::solve(&solver).success());
+        let finding = solve(&solver);
+        assert_proven(&solver, &finding);
+        assert_eq!(finding.get_mine_fields(), &vec![(2, 1), (3, 1)]);
+        for field in [(4, 0), (6, 0), (8, 0), (8, 1), (8, 2)] {
+            assert!(finding.get_safe_fields().contains(&field));
+        }
+        assert_eq!(
+            finding.get_explanations()[0].render(),
+            "the regions of the 2 at (5,1), the 2 at (6,1), the 1 at (7,1), the 3 at (3,2) bound the mines: {(2,1), (3,1)} are mines"
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use super::sat_solver::{
+    build_constraints, enumerate_solutions_within, find_independent_components,
+};
+use super::{Explanation, Finding, Rule, Solver};
+use crate::solver::CellState;
+use std::collections::HashSet;
+
+/*
+Global mine count strategy:
+
+The SAT strategy solves every independent component on its own and only uses the global mine count
+as an upper bound. This strategy combines the components:
+- Every component is enumerated once, collecting which mine counts it can have
+  and which cells are fixed for each of those mine counts.
+- Hidden cells without a revealed neighbour (interior cells) can take any number of the remaining mines.
+- A mine count of a component is only possible if the other components can fill up the rest,
+  so that between 0 and (interior cells) mines are left for the interior.
+- Cells which are fixed for all possible mine counts of their component are solved.
+- If all possible totals leave no mine (or only mines) for the interior, the interior cells are solved.
+Every component is needed for the totals, so the strategy gives up as soon as one of them runs out of budget.
+*/
+
+pub fn solve(solver: &Solver) -> Finding {
+    let mut finding = Finding::new();
+
+    let remaining_mines = solver.get_remaining_mines() as usize;
+    let components = find_independent_components(solver);
+
+    let border: HashSet<(u32, u32)> = components.iter().flatten().copied().collect();
+    let interior: Vec<(u32, u32)> = solver
+        .sorted_fields()
+        .filter(|&(x, y)| matches!(solver.get_state(x, y), CellState::Hidden(_)))
+        .filter(|field| !border.contains(field))
+        .collect();
+
+    let solutions: Result<Vec<_>, _> = components
+        .iter()
+        .map(|component| {
+            let constraints = build_constraints(solver, component);
+            enumerate_solutions_within(
+                component,
+                &constraints,
+                remaining_mines as u32,
+                solver.config.get_budget(),
+                &solver.search_counters,
+            )
+        })
+        .collect();
+    let solutions = match solutions {
+        Ok(solutions) => solutions,
+        Err(reason) => {
+            finding.give_up(reason);
+            return finding;
+        }
+    };
+
+    let possible: Vec<Vec<bool>> = solutions
+        .iter()
+        .map(|s| s.solution_counts.iter().map(|&c| c > 0.0).collect())
+        .collect();
+
+    // Totals which leave a valid amount of mines for the interior
+    let valid_total =
+        |total: usize| total <= remaining_mines && remaining_mines - total <= interior.len();
+
+    // prefix[i] / suffix[i]: reachable mine totals of all components before / from i
+    let mut prefix = vec![vec![true]];
+    for p in &possible {
+        let next = reachable_sums(prefix.last().unwrap(), p);
+        prefix.push(next);
+    }
+    let mut suffix = vec![vec![true]; possible.len() + 1];
+    for i in (0..possible.len()).rev() {
+        suffix[i] = reachable_sums(&suffix[i + 1], &possible[i]);
+    }
+
+    for (i, (component, solution)) in components.iter().zip(&solutions).enumerate() {
+        let others = reachable_sums(&prefix[i], &suffix[i + 1]);
+
+        let allowed: Vec<usize> = (0..possible[i].len())
+            .filter(|&k| possible[i][k])
+            .filter(|&k| {
+                others
+                    .iter()
+                    .enumerate()
+                    .any(|(other, &reachable)| reachable && valid_total(k + other))
+            })
+            .collect();
+
+        if allowed.is_empty() {
+            continue;
+        }
+
+        let mut component_finding = Finding::new();
+        for (cell, &field) in component.iter().enumerate() {
+            let always_mine = allowed
+                .iter()
+                .all(|&k| solution.safe_counts[k][cell] == 0.0);
+            let never_mine = allowed
+                .iter()
+                .all(|&k| solution.mine_counts[k][cell] == 0.0);
+
+            if always_mine {
+                component_finding.add_mine_field(field);
+            } else if never_mine {
+                component_finding.add_safe_field(field);
+            }
+        }
+
+        if component_finding.success() {
+            finding.add_explanation(Explanation::new(
+                Rule::GlobalCount {
+                    remaining_mines: remaining_mines as u32,
+                },
+                solver.constraint_cells_of(component),
+                component_finding.get_mine_fields().clone(),
+                component_finding.get_safe_fields().clone(),
+            ));
+            finding.add_mine_fields(component_finding.get_mine_fields().clone());
+            finding.add_safe_fields(component_finding.get_safe_fields().clone());
+        }
+    }
+
+    if !interior.is_empty() {
+        let totals: Vec<usize> = (0..prefix[possible.len()].len())
+            .filter(|&total| prefix[possible.len()][total] && valid_total(total))
+            .collect();
+
+        if !totals.is_empty() {
+            let rule = Rule::GlobalCount {
+                remaining_mines: remaining_mines as u32,
+            };
+            let constraints = solver.frontier().map(|(x, y)| solver.constraint_cell(x, y));
+
+            if totals.iter().all(|&total| total == remaining_mines) {
+                finding.add_explanation(Explanation::new(
+                    rule,
+                    constraints.collect(),
+                    vec![],
+                    interior.clone(),
+                ));
+                finding.add_safe_fields(interior);
+            } else if totals
+                .iter()
+                .all(|&total| remaining_mines - total == interior.len())
+            {
+                finding.add_explanation(Explanation::new(
+                    rule,
+                    constraints.collect(),
+                    interior.clone(),
+                    vec![],
+                ));
+                finding.add_mine_fields(interior);
+            }
+        }
+    }
+
+    finding
+}
+
+fn reachable_sums(a: &[bool], b: &[bool]) -> Vec<bool> {
+    let mut result = vec![false; a.len() + b.len() - 1];
+    for (i, _) in a.iter().enumerate().filter(|(_, reachable)| **reachable) {
+        for (j, _) in b.iter().enumerate().filter(|(_, reachable)| **reachable) {
+            result[i + j] = true;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::sat_solver::tests::{create_solver_with_reveals, create_test_field};
+    use super::*;
+
+    #[test]
+    fn test_component_mine_count_forced() {
+        // Either one mine in the middle or one mine in each corner,
+        // only the corners use up both mines of the field
+        let pattern = "
+            M?M
+            1?1
+        ";
+
+        let field = create_test_field(pattern);
+        let solver = create_solver_with_reveals(&field, &[(0, 1), (2, 1)]);
+
+        let sat_finding = super::super::sat_solver::solve(&solver);
+        assert!(!sat_finding.success(), "SAT alone can't decide this");
+
+        let finding = solve(&solver);
+
+        assert_eq!(finding.get_mine_fields().len(), 2);
+        assert!(finding.get_mine_fields().contains(&(0, 0)));
+        assert!(finding.get_mine_fields().contains(&(2, 0)));
+        assert_eq!(finding.get_safe_fields().len(), 2);
+    }
+
+    #[test]
+    fn test_interior_safe() {
+        // The only mine is in the 50/50 next to the 1, so the far cell is safe
+        let pattern = "
+            M.??
+        ";
+
+        let field = create_test_field(pattern);
+        let solver = create_solver_with_reveals(&field, &[(1, 0)]);
+
+        let finding = solve(&solver);
+
+        assert_eq!(finding.get_safe_fields(), &vec![(3, 0)]);
+        assert!(finding.get_mine_fields().is_empty());
+    }
+
+    #[test]
+    fn test_interior_mines() {
+        // The 1 takes exactly one mine, the other two have to be in the interior
+        let pattern = "
+            M.?MM
+        ";
+
+        let field = create_test_field(pattern);
+        let solver = create_solver_with_reveals(&field, &[(1, 0)]);
+
+        let finding = solve(&solver);
+
+        assert_eq!(finding.get_mine_fields(), &vec![(3, 0), (4, 0)]);
+        assert!(finding.get_safe_fields().is_empty());
+    }
+
+    #[test]
+    fn test_separate_islands() {
+        // Two islands, each with one mine in the middle or one in each corner.
+        // With two mines in total both islands must use the middle, so all corners are safe
+        let pattern = "
+            ?M?.?M?
+            1?1.1?1
+        ";
+
+        let field = create_test_field(pattern);
+        let solver =
+            create_solver_with_reveals(&field, &[(0, 1), (2, 1), (3, 0), (3, 1), (4, 1), (6, 1)]);
+
+        assert_eq!(find_independent_components(&solver).len(), 2);
+
+        let finding = solve(&solver);
+
+        assert!(finding.get_mine_fields().is_empty());
+        assert_eq!(finding.get_safe_fields().len(), 4);
+        for corner in [(0, 0), (2, 0), (4, 0), (6, 0)] {
+            assert!(finding.get_safe_fields().contains(&corner));
+        }
+    }
+}
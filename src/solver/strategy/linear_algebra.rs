@@ -0,0 +1,313 @@
+use super::sat_solver::find_independent_components;
+use super::{Explanation, Finding, Rule, Solver};
+use std::collections::{HashMap, HashSet};
+
+/*
+Linear algebra strategy:
+
+Every number of a component is an equation over its hidden neighbours, each of them 0 (safe) or 1 (mine):
+  x1 + x2 + x3 = reduced count
+The equations of a component are brought into reduced row echelon form with integer Gauss-Jordan elimination,
+each row remembers with which factor every original number went into it.
+A reduced row  a1*x1 + ... + an*xn = b  can reach at most the sum of its positive factors (max)
+and at least the sum of its negative factors (min). For a cell with factor a:
+- if b > max - |a|, the row can't be met without the cell's share of max, the cell takes that value
+  (a mine for a positive factor, safe for a negative one)
+- if b < min + |a|, the same holds the other way round
+A row with b = max or b = min pins all of its cells at once.
+This is polynomial and catches combinations of numbers too far apart for the reduction strategy,
+it is still weaker than the SAT search: the row echelon form is only one of many ways to combine the numbers.
+*/
+
+pub fn solve(solver: &Solver) -> Finding {
+    let mut finding = Finding::new();
+
+    let dirty: HashSet<(u32, u32)> = solver.dirty_frontier(1).into_iter().collect();
+    if dirty.is_empty() {
+        return finding;
+    }
+
+    for mut component in find_independent_components(solver) {
+        // The echelon form depends on the column order, sorting keeps the deductions reproducible
+        component.sort_unstable_by_key(|&(x, y)| (y, x));
+        let columns: HashMap<(u32, u32), usize> = component
+            .iter()
+            .enumerate()
+            .map(|(i, &field)| (field, i))
+            .collect();
+
+        let numbers: Vec<(u32, u32)> = solver
+            .frontier()
+            .filter(|&(x, y)| {
+                solver
+                    .get_surrounding_unrevealed(x, y)
+                    .iter()
+                    .any(|field| columns.contains_key(field))
+            })
+            .collect();
+        if !numbers.iter().any(|number| dirty.contains(number)) {
+            continue;
+        }
+
+        let rows: Vec<Row> = numbers
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| {
+                let mut coefficients = vec![0; component.len()];
+                for field in solver.get_surrounding_unrevealed(x, y) {
+                    coefficients[columns[&field]] = 1;
+                }
+                let mut sources = vec![0; numbers.len()];
+                sources[i] = 1;
+                Row {
+                    coefficients,
+                    value: solver.get_reduced_count(x, y) as i64,
+                    sources,
+                }
+            })
+            .collect();
+
+        // Coefficients which outgrow i64 only come from contrived boards, SAT still handles those
+        let Some(rows) = row_reduce(rows, component.len()) else {
+            continue;
+        };
+
+        for row in rows {
+            let (mines, safe) = row.forced_cells();
+            if mines.is_empty() && safe.is_empty() {
+                continue;
+            }
+
+            let mines: Vec<(u32, u32)> = mines.into_iter().map(|i| component[i]).collect();
+            let safe: Vec<(u32, u32)> = safe.into_iter().map(|i| component[i]).collect();
+            let constraints = numbers
+                .iter()
+                .zip(&row.sources)
+                .filter(|&(_, &factor)| factor != 0)
+                .map(|(&(x, y), _)| solver.constraint_cell(x, y))
+                .collect();
+
+            finding.add_explanation(Explanation::new(
+                Rule::LinearCombination,
+                constraints,
+                mines.clone(),
+                safe.clone(),
+            ));
+            finding.add_mine_fields(mines);
+            finding.add_safe_fields(safe);
+        }
+    }
+
+    finding
+}
+
+/// An equation over the cells of a component, together with the factors of the numbers it was combined from
+#[derive(Debug, Clone)]
+struct Row {
+    coefficients: Vec<i64>,
+    value: i64,
+    sources: Vec<i64>,
+}
+
+impl Row {
+    /// `self = factor * self - other_factor * other`, reduced by the common divisor.
+    /// None if a coefficient doesn't fit into an i64.
+    fn eliminate(&mut self, factor: i64, other: &Row, other_factor: i64) -> Option<()> {
+        let combine = |a: i64, b: i64| {
+            a.checked_mul(factor)?
+                .checked_sub(b.checked_mul(other_factor)?)
+        };
+
+        for (a, &b) in self.coefficients.iter_mut().zip(&other.coefficients) {
+            *a = combine(*a, b)?;
+        }
+        for (a, &b) in self.sources.iter_mut().zip(&other.sources) {
+            *a = combine(*a, b)?;
+        }
+        self.value = combine(self.value, other.value)?;
+
+        let divisor = self
+            .coefficients
+            .iter()
+            .chain(&self.sources)
+            .chain([&self.value])
+            .fold(0, |g, &v| gcd(g, v.abs()));
+        if divisor > 1 {
+            self.coefficients.iter_mut().for_each(|v| *v /= divisor);
+            self.sources.iter_mut().for_each(|v| *v /= divisor);
+            self.value /= divisor;
+        }
+        Some(())
+    }
+
+    /// Columns which are a mine and columns which are safe in every 0/1 solution of the row
+    fn forced_cells(&self) -> (Vec<usize>, Vec<usize>) {
+        let max: i64 = self.coefficients.iter().filter(|&&a| a > 0).sum();
+        let min: i64 = self.coefficients.iter().filter(|&&a| a < 0).sum();
+
+        let mut mines = Vec::new();
+        let mut safe = Vec::new();
+        // Rows outside of their bounds only happen on contradicting boards, they don't prove anything
+        if self.value > max || self.value < min {
+            return (mines, safe);
+        }
+
+        for (i, &a) in self.coefficients.iter().enumerate() {
+            if a == 0 {
+                continue;
+            }
+            let mine_reaches_max = a > 0;
+            if self.value > max - a.abs() {
+                if mine_reaches_max {
+                    mines.push(i);
+                } else {
+                    safe.push(i);
+                }
+            } else if self.value < min + a.abs() {
+                if mine_reaches_max {
+                    safe.push(i);
+                } else {
+                    mines.push(i);
+                }
+            }
+        }
+        (mines, safe)
+    }
+}
+
+/// Gauss-Jordan elimination, every pivot column ends up with a single non-zero row
+fn row_reduce(mut rows: Vec<Row>, columns: usize) -> Option<Vec<Row>> {
+    let mut rank = 0;
+    for column in 0..columns {
+        let Some(pivot) = (rank..rows.len()).find(|&r| rows[r].coefficients[column] != 0) else {
+            continue;
+        };
+        rows.swap(rank, pivot);
+
+        let pivot_row = rows[rank].clone();
+        let pivot_value = pivot_row.coefficients[column];
+        for (r, row) in rows.iter_mut().enumerate() {
+            let value = row.coefficients[column];
+            if r != rank && value != 0 {
+                row.eliminate(pivot_value, &pivot_row, value)?;
+            }
+        }
+        rank += 1;
+    }
+    Some(rows)
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::reference::reference_deductions;
+    use crate::solver::{VisibleCell, strategy::sat_solver};
+    use crate::{DefinedField, SolveOutcome, SolverConfig, SolvingStrategy, create_solver};
+
+    fn row(coefficients: Vec<i64>, value: i64) -> Row {
+        Row {
+            sources: vec![1],
+            coefficients,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_forced_cells() {
+        // x0 + x1 - x2 = 2: x0 and x1 are mines, x2 is safe
+        assert_eq!(row(vec![1, 1, -1], 2).forced_cells(), (vec![0, 1], vec![2]));
+        // x0 - x1 - x2 = -2: x0 is safe, x1 and x2 are mines
+        assert_eq!(
+            row(vec![1, -1, -1], -2).forced_cells(),
+            (vec![1, 2], vec![0])
+        );
+        // 2*x0 + x1 + x2 = 3 doesn't pin every cell, but x0 = 0 would need three mines in two cells
+        assert_eq!(row(vec![2, 1, 1], 3).forced_cells(), (vec![0], vec![]));
+        assert_eq!(row(vec![1, 1, -1], 1).forced_cells(), (vec![], vec![]));
+        // Contradicting rows prove nothing
+        assert_eq!(row(vec![1, 1], 3).forced_cells(), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_row_reduce() {
+        // x0 + x1 = 1, x1 + x2 = 1, x0 + x1 + x2 = 1 has the single solution x1 = 1
+        let rows = vec![
+            row(vec![1, 1, 0], 1),
+            row(vec![0, 1, 1], 1),
+            row(vec![1, 1, 1], 1),
+        ];
+        let rows = row_reduce(rows, 3).unwrap();
+        let mines: Vec<usize> = rows.iter().flat_map(|r| r.forced_cells().0).collect();
+        let safe: Vec<usize> = rows.iter().flat_map(|r| r.forced_cells().1).collect();
+        assert_eq!(mines, vec![1]);
+        assert_eq!(safe, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_combines_numbers() {
+        // . 1 ?
+        // 1 2 ?
+        // ? ? ?
+        // The two 1s cover disjoint parts of the 2, so the corner (2,2) is safe.
+        // No pair of numbers shows this, the reduction strategy is stuck.
+        let h = VisibleCell::Hidden;
+        let cells = vec![
+            vec![VisibleCell::Revealed(0), VisibleCell::Revealed(1), h],
+            vec![VisibleCell::Revealed(1), VisibleCell::Revealed(2), h],
+            vec![h, h, h],
+        ];
+        let cheap = SolverConfig::new(vec![
+            SolvingStrategy::Simple,
+            SolvingStrategy::Reduction,
+            SolvingStrategy::Patterns,
+        ]);
+        assert!(
+            crate::Solver::from_visible(&cells, 2, &cheap)
+                .unwrap()
+                .hint()
+                .is_none()
+        );
+
+        let solver = crate::Solver::from_visible(&cells, 2, &SolverConfig::default()).unwrap();
+        let finding = solve(&solver);
+        assert_eq!(finding.get_safe_fields(), &vec![(2, 2)]);
+        assert!(finding.get_mine_fields().is_empty());
+
+        let reference = reference_deductions(&solver);
+        assert!(reference.safe.contains(&(2, 2)));
+
+        let explanation = &finding.get_explanations()[0];
+        assert_eq!(explanation.rule, Rule::LinearCombination);
+        assert_eq!(explanation.constraints.len(), 3);
+        assert_eq!(
+            explanation.render(),
+            "combining the 1 at (1,0), the 1 at (0,1), the 2 at (1,1) leaves one way: {(2,2)} is safe"
+        );
+    }
+
+    #[test]
+    fn test_extended_box_logic() {
+        let field = DefinedField::from_file("generated/testing/extended_box_logic.minesweeper")
+            .expect("Failed to load testing file");
+
+        // The opening leaves a single region of many layouts, nothing can be proven there.
+        // The elimination must not claim otherwise and agrees with the complete search.
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
+        assert!(reference_deductions(&solver).is_empty());
+        assert!(!solve(&solver).success());
+        assert!(!sat_solver::solve(&solver).success());
+
+        let config = SolverConfig::new(vec![
+            SolvingStrategy::Simple,
+            SolvingStrategy::Reduction,
+            SolvingStrategy::LinearAlgebra,
+        ]);
+        let mut solver = crate::Solver::new(&field, &config);
+        assert_eq!(solver.solve(), Ok(SolveOutcome::Stuck));
+    }
+}
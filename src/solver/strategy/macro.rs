@@ -1,6 +1,6 @@
 macro_rules! define_strategies {
     ($($variant:ident => $module:ident),* $(,)?) => {
-        #[derive(Copy, Clone, Debug)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
         pub enum SolvingStrategy {
             $($variant),*
         }
@@ -16,11 +16,17 @@ macro_rules! define_strategies {
                 }
             }
 
+            fn get_name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => stringify!($variant)),*
+                }
+            }
+
             pub fn iter() -> impl Iterator<Item = SolvingStrategy> {
                 Self::ALL.iter().copied()
             }
 
-            pub fn execute(&self, solver: &Solver) -> Finding {
+            fn execute(&self, solver: &Solver) -> Finding {
                 self.get_method()(solver)
             }
         }
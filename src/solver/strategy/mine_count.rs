@@ -1,26 +1,87 @@
-use super::{Finding, Solver};
+use super::{Explanation, Finding, Rule, Solver};
 use crate::solver::CellState;
 
 /*
 Mine count strategy:
 
 If the total number of remaining unflagged mines equals zero, all hidden cells are safe.
-This catches end-game states where all mines are accounted for by flags but some
-hidden cells haven't been opened yet because they were never adjacent to a revealed number.
+If it equals the number of hidden cells, all hidden cells are mines.
+This catches end-game states where hidden cells haven't been solved yet because they were
+never adjacent to a revealed number, like islands which are fully enclosed by mines.
 */
 
 pub fn solve(solver: &Solver) -> Finding {
     let mut finding = Finding::new();
 
-    if solver.get_remaining_mines() != 0 {
+    let hidden: Vec<(u32, u32)> = solver
+        .sorted_fields()
+        .filter(|&(x, y)| matches!(solver.get_state(x, y), CellState::Hidden(_)))
+        .collect();
+
+    if hidden.is_empty() {
         return finding;
     }
 
-    for (x, y) in solver.sorted_fields() {
-        if matches!(solver.get_state(x, y), CellState::Hidden(_)) {
-            finding.add_safe_field((x, y));
-        }
+    let remaining_mines = solver.get_remaining_mines();
+
+    let rule = Rule::GlobalCount { remaining_mines };
+    if remaining_mines == 0 {
+        finding.add_explanation(Explanation::new(rule, vec![], vec![], hidden.clone()));
+        finding.add_safe_fields(hidden);
+    } else if remaining_mines == hidden.len() as u32 {
+        finding.add_explanation(Explanation::new(rule, vec![], hidden.clone(), vec![]));
+        finding.add_mine_fields(hidden);
     }
 
     finding
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::sat_solver::tests::{create_solver_with_reveals, create_test_field};
+    use super::*;
+    use crate::MineSweeperField;
+
+    #[test]
+    fn test_no_mines_remaining() {
+        let pattern = "
+            M..
+            ...
+        ";
+
+        let field = create_test_field(pattern);
+        let mut solver = create_solver_with_reveals(&field, &[(1, 0), (2, 0)]);
+        solver.set_state(0, 0, CellState::Flagged(field.get_cell(0, 0).clone()));
+
+        let finding = solve(&solver);
+
+        assert_eq!(finding.get_safe_fields(), &vec![(0, 1), (1, 1), (2, 1)]);
+        assert!(finding.get_mine_fields().is_empty());
+    }
+
+    #[test]
+    fn test_enclosed_island_full_of_mines() {
+        // The bottom row touches no revealed number, but only mines are left
+        let pattern = "
+            ...
+            ...
+            MMM
+        ";
+
+        let field = create_test_field(pattern);
+        let solver = create_solver_with_reveals(&field, &[(0, 0), (1, 0), (2, 0)]);
+
+        let finding = solve(&solver);
+
+        assert!(finding.get_safe_fields().is_empty());
+        assert!(finding.get_mine_fields().is_empty());
+
+        let solver =
+            create_solver_with_reveals(&field, &[(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+
+        let finding = solve(&solver);
+
+        assert_eq!(finding.get_mine_fields(), &vec![(0, 2), (1, 2), (2, 2)]);
+        assert!(finding.get_safe_fields().is_empty());
+    }
+}
@@ -1,29 +1,78 @@
 #[macro_use]
 mod r#macro;
-use super::{Finding, Solver};
+use super::{Explanation, Finding, GiveUpReason, Rule, Solver, SolverView};
 
+mod box_logic;
+mod global_count;
+mod linear_algebra;
 mod mine_count;
+mod patterns;
 mod reduction;
-mod sat_solver;
+pub(super) mod sat_solver;
 mod simple;
+mod r#trait;
+
+pub use r#trait::Strategy;
 
 define_strategies! {
     Simple => simple,
-    Complex => reduction,
+    Reduction => reduction,
+    Patterns => patterns,
+    BoxLogic => box_logic,
+    LinearAlgebra => linear_algebra,
     Sat => sat_solver,
+    GlobalCount => global_count,
     MineCount => mine_count,
 }
 
+/*
+Strategies only look at numbers near a change since their last run (dirty_frontier),
+the others were already tried on the same cells and can't give something new.
+*/
+
 /*
 Current State:
 
 - Solver is not able to solve every solveable field
-  - fields where islands which are not accessible / are completely surrounded by mines
+  - islands enclosed by mines are only solved by counting the remaining mines (MineCount, GlobalCount),
+    the no-guess generator opens the others by moving a mine of their wall inside
   - MineCount strategy could be improved
-  - fields with multiple 50/50 islands which are not bordering each other but are still solveable with minecount
-  -> could be solved with so called meta ng strategies
 - Solver could be optimized for even more for performance
   - gpu / shader computing for permutations / more parallelism
   - more strategies which can solve small islands possibly
-  - strategy for pattern recognition (1-3-1 corner etc) so the sat doesnt have to solve them
 */
+
+#[cfg(test)]
+mod tests {
+    use crate::{DefinedField, SolveOutcome, Solver, SolverConfig, SolvingStrategy};
+
+    #[test]
+    fn test_saves_sat_runs() {
+        let sat_runs = |field: &DefinedField, config: &SolverConfig| {
+            let mut solver = Solver::new(field, config);
+            assert_eq!(solver.solve(), Ok(SolveOutcome::Solved));
+            let stats = solver.get_stats();
+            let sat = stats.strategies.iter().find(|s| s.name == "Sat").unwrap();
+            sat.invocations
+        };
+        let without = |excluded: &[SolvingStrategy]| {
+            SolverConfig::new(
+                SolvingStrategy::iter()
+                    .filter(|s| !excluded.contains(s))
+                    .collect(),
+            )
+        };
+
+        // Each of the polynomial strategies on its own takes over part of the work of the SAT search
+        let neither = without(&[SolvingStrategy::BoxLogic, SolvingStrategy::LinearAlgebra]);
+        let box_logic = without(&[SolvingStrategy::LinearAlgebra]);
+        let linear_algebra = without(&[SolvingStrategy::BoxLogic]);
+        for name in ["hard", "evil_ng_field"] {
+            let field = DefinedField::from_file(&format!("generated/testing/{}.minesweeper", name))
+                .expect("Failed to load testing file");
+            let baseline = sat_runs(&field, &neither);
+            assert!(sat_runs(&field, &box_logic) < baseline);
+            assert!(sat_runs(&field, &linear_algebra) < baseline);
+        }
+    }
+}
@@ -0,0 +1,608 @@
+use super::sat_solver::{Constraint, enumerate_solutions};
+use super::{Explanation, Finding, Rule, Solver};
+use crate::solver::CellState;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/*
+Pattern strategy:
+
+Matches well known shapes (see generated/patterns/) against the visible board, in all rotations and mirrors.
+A template is a small grid:
+- '1'-'8': revealed number with this reduced count (flags already subtracted)
+- '?': hidden cell
+- '#': no hidden cell (revealed, flagged or outside of the board)
+- '*': anything, same as every cell outside of the template
+Numbers whose neighbours are all part of the template give an exact constraint. Numbers with
+neighbours outside of the template only give an upper bound for the hidden cells of the template.
+The deductions of every template are computed once by enumerating its constraints, so a template can't
+claim more than follows from it. If a bounded number is always satisfied by the template cells,
+its other hidden neighbours on the board are safe as well.
+
+B1 is the basic shape of a number needing all its hidden neighbours. H2 and H3 are holes:
+a revealed number inside a hidden row, and two 1s sharing the same two hidden cells across a row.
+Their examples only show these shapes after the first deductions, both end in 50/50s.
+*/
+
+const TEMPLATES: [(&str, &str); 8] = [
+    (
+        "B1",
+        "
+        ##?
+        #3?
+        ##?
+        ",
+    ),
+    (
+        "1-1",
+        "
+        ###
+        #11
+        #??
+        ",
+    ),
+    (
+        "1-2",
+        "
+        ###
+        12#
+        ???
+        ",
+    ),
+    (
+        "1-2-1",
+        "
+        ###
+        121
+        ???
+        ",
+    ),
+    (
+        "1-2-2-1",
+        "
+        ####
+        1221
+        ????
+        ",
+    ),
+    (
+        "1-3-1",
+        "
+        #1?
+        13?
+        ???
+        ",
+    ),
+    (
+        "H2",
+        "
+        ###
+        #1#
+        ?1?
+        ???
+        ",
+    ),
+    (
+        "H3",
+        "
+        ###
+        #1#
+        #??
+        #1?
+        ???
+        ",
+    ),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum TemplateCell {
+    Number(u8),
+    Hidden,
+    Blocked,
+    Any,
+}
+
+/// One rotation / mirror of a template, with all positions relative to its anchor number
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Variant {
+    cells: Vec<((i32, i32), TemplateCell)>,
+    mines: Vec<(i32, i32)>,
+    safes: Vec<(i32, i32)>,
+    /// Numbers which are satisfied by the template cells, their other hidden neighbours are safe
+    saturated: Vec<(i32, i32)>,
+}
+
+struct Template {
+    name: &'static str,
+    anchor: u8,
+    variants: Vec<Variant>,
+}
+
+pub fn solve(solver: &Solver) -> Finding {
+    let mut finding = Finding::new();
+
+    // A match only depends on the template cells and the neighbours of its numbers
+    let reach = templates()
+        .iter()
+        .flat_map(|template| &template.variants)
+        .flat_map(|variant| &variant.cells)
+        .map(|&((dx, dy), _)| dx.unsigned_abs().max(dy.unsigned_abs()))
+        .max()
+        .unwrap_or(0) as u8
+        + 1;
+
+    for (x, y) in solver.dirty_frontier(reach) {
+        let reduced_count = solver.get_reduced_count(x, y);
+
+        for template in templates() {
+            if template.anchor != reduced_count {
+                continue;
+            }
+
+            for variant in &template.variants {
+                if !matches(solver, variant, x, y) {
+                    continue;
+                }
+
+                let hidden: HashSet<(u32, u32)> = variant
+                    .cells
+                    .iter()
+                    .filter(|(_, cell)| *cell == TemplateCell::Hidden)
+                    .filter_map(|&(offset, _)| position(solver, x, y, offset))
+                    .collect();
+
+                let mines: Vec<(u32, u32)> = variant
+                    .mines
+                    .iter()
+                    .map(|&offset| position(solver, x, y, offset).unwrap())
+                    .collect();
+                let mut safes: Vec<(u32, u32)> = variant
+                    .safes
+                    .iter()
+                    .map(|&offset| position(solver, x, y, offset).unwrap())
+                    .collect();
+                for &offset in &variant.saturated {
+                    let (nx, ny) = position(solver, x, y, offset).unwrap();
+                    let others = solver
+                        .get_surrounding_unrevealed(nx, ny)
+                        .into_iter()
+                        .filter(|field| !hidden.contains(field) && !safes.contains(field));
+                    safes.extend(others.collect::<Vec<_>>());
+                }
+
+                let constraints = variant
+                    .cells
+                    .iter()
+                    .filter(|(_, cell)| matches!(cell, TemplateCell::Number(_)))
+                    .filter_map(|&(offset, _)| position(solver, x, y, offset))
+                    .map(|(nx, ny)| solver.constraint_cell(nx, ny))
+                    .collect();
+
+                finding.add_mine_fields(mines.clone());
+                finding.add_safe_fields(safes.clone());
+                if !mines.is_empty() || !safes.is_empty() {
+                    finding.add_explanation(Explanation::new(
+                        Rule::Pattern(template.name),
+                        constraints,
+                        mines,
+                        safes,
+                    ));
+                }
+                finding.add_pattern(template.name);
+            }
+        }
+    }
+
+    finding
+}
+
+fn position(solver: &Solver, x: u32, y: u32, (dx, dy): (i32, i32)) -> Option<(u32, u32)> {
+    let nx = x as i64 + dx as i64;
+    let ny = y as i64 + dy as i64;
+
+    if nx < 0 || ny < 0 || nx >= solver.width as i64 || ny >= solver.height as i64 {
+        None
+    } else {
+        Some((nx as u32, ny as u32))
+    }
+}
+
+fn matches(solver: &Solver, variant: &Variant, x: u32, y: u32) -> bool {
+    variant.cells.iter().all(|&(offset, cell)| {
+        let position = position(solver, x, y, offset);
+
+        match (cell, position) {
+            (TemplateCell::Any, _) => true,
+            (TemplateCell::Blocked, None) => true,
+            (TemplateCell::Blocked, Some((nx, ny))) => {
+                !matches!(solver.get_state(nx, ny), CellState::Hidden(_))
+            }
+            (TemplateCell::Hidden, Some((nx, ny))) => {
+                matches!(solver.get_state(nx, ny), CellState::Hidden(_))
+            }
+            (TemplateCell::Number(n), Some((nx, ny))) => {
+                solver.has_informations(nx, ny) && solver.get_reduced_count(nx, ny) == n
+            }
+            (_, None) => false,
+        }
+    })
+}
+
+fn templates() -> &'static [Template] {
+    static TEMPLATES_CELL: OnceLock<Vec<Template>> = OnceLock::new();
+
+    TEMPLATES_CELL.get_or_init(|| {
+        TEMPLATES
+            .iter()
+            .map(|&(name, pattern)| build_template(name, pattern))
+            .collect()
+    })
+}
+
+fn build_template(name: &'static str, pattern: &str) -> Template {
+    let grid = parse(pattern);
+    let base = deduce(&grid);
+
+    // All 8 rotations and mirrors, symmetric templates produce duplicates
+    let mut variants: Vec<Variant> = Vec::new();
+    for transform in 0..8 {
+        let variant = transform_variant(&base, transform);
+        if !variants.contains(&variant) {
+            variants.push(variant);
+        }
+    }
+
+    let anchor = match base.cells[0].1 {
+        TemplateCell::Number(n) => n,
+        _ => unreachable!("templates are anchored at a number"),
+    };
+
+    Template {
+        name,
+        anchor,
+        variants,
+    }
+}
+
+fn parse(pattern: &str) -> Vec<((i32, i32), TemplateCell)> {
+    let mut grid = Vec::new();
+
+    for (y, line) in pattern.trim().lines().enumerate() {
+        for (x, ch) in line.trim().chars().enumerate() {
+            let cell = match ch {
+                '1'..='8' => TemplateCell::Number(ch as u8 - b'0'),
+                '?' => TemplateCell::Hidden,
+                '#' => TemplateCell::Blocked,
+                '*' => TemplateCell::Any,
+                _ => panic!("Invalid template character '{}'", ch),
+            };
+            grid.push(((x as i32, y as i32), cell));
+        }
+    }
+
+    grid
+}
+
+/// Enumerates the constraints of the template and collects everything that holds in all solutions.
+/// Returns the template anchored at its highest number.
+fn deduce(grid: &[((i32, i32), TemplateCell)]) -> Variant {
+    let cell_at = |pos: (i32, i32)| {
+        grid.iter()
+            .find(|(p, _)| *p == pos)
+            .map_or(TemplateCell::Any, |(_, cell)| *cell)
+    };
+    let to_field = |(x, y): (i32, i32)| (x as u32, y as u32);
+
+    let hidden: Vec<(i32, i32)> = grid
+        .iter()
+        .filter(|(_, cell)| *cell == TemplateCell::Hidden)
+        .map(|(pos, _)| *pos)
+        .collect();
+    let mut component: Vec<(u32, u32)> = hidden.iter().copied().map(to_field).collect();
+
+    let mut constraints = Vec::new();
+    // Slack cells of bounded numbers, placed far outside the template
+    let mut slack = Vec::new();
+
+    for &(pos, cell) in grid {
+        let TemplateCell::Number(n) = cell else {
+            continue;
+        };
+
+        let neighbours: Vec<(i32, i32)> = (-1..=1)
+            .flat_map(|dx| (-1..=1).map(move |dy| (pos.0 + dx, pos.1 + dy)))
+            .filter(|&neighbour| neighbour != pos)
+            .collect();
+
+        let mut fields: Vec<(u32, u32)> = neighbours
+            .iter()
+            .filter(|&&neighbour| cell_at(neighbour) == TemplateCell::Hidden)
+            .copied()
+            .map(to_field)
+            .collect();
+
+        // Unknown neighbours can hold up to n mines, modelled as n extra cells
+        if neighbours
+            .iter()
+            .any(|&neighbour| cell_at(neighbour) == TemplateCell::Any)
+        {
+            let extra: Vec<(u32, u32)> = (0..n as u32)
+                .map(|i| (1000 + slack.len() as u32, i))
+                .collect();
+            fields.extend(&extra);
+            component.extend(&extra);
+            slack.push((pos, extra));
+        }
+
+        constraints.push(Constraint {
+            mine_count: n as u32,
+            fields,
+        });
+    }
+
+    let solutions = enumerate_solutions(&component, &constraints, component.len() as u32);
+    let total =
+        |counts: &[Vec<f64>], index: usize| -> f64 { counts.iter().map(|row| row[index]).sum() };
+    let index_of = |field: &(u32, u32)| component.iter().position(|f| f == field).unwrap();
+
+    assert!(
+        solutions.solution_counts.iter().any(|&count| count > 0.0),
+        "Template has no valid solution"
+    );
+
+    let mut mines = Vec::new();
+    let mut safes = Vec::new();
+    for &pos in &hidden {
+        let index = index_of(&to_field(pos));
+        if total(&solutions.safe_counts, index) == 0.0 {
+            mines.push(pos);
+        } else if total(&solutions.mine_counts, index) == 0.0 {
+            safes.push(pos);
+        }
+    }
+
+    let saturated: Vec<(i32, i32)> = slack
+        .iter()
+        .filter(|(_, extra)| {
+            extra
+                .iter()
+                .all(|field| total(&solutions.mine_counts, index_of(field)) == 0.0)
+        })
+        .map(|(pos, _)| *pos)
+        .collect();
+
+    // Anchored at the highest number: fewer board cells to try, and mirrored
+    // variants of symmetric templates end up identical
+    let anchor = grid
+        .iter()
+        .filter_map(|&(pos, cell)| match cell {
+            TemplateCell::Number(n) => Some((n, pos)),
+            _ => None,
+        })
+        .rev()
+        .max_by_key(|&(n, _)| n)
+        .map(|(_, pos)| pos)
+        .expect("Template without a number");
+
+    let relative = |(x, y): (i32, i32)| (x - anchor.0, y - anchor.1);
+    let mut cells: Vec<((i32, i32), TemplateCell)> = grid
+        .iter()
+        .map(|&(pos, cell)| (relative(pos), cell))
+        .collect();
+    // The anchor comes first, it is matched by the caller
+    cells.sort_by_key(|&(pos, _)| pos != (0, 0));
+
+    Variant {
+        cells,
+        mines: mines.into_iter().map(relative).collect(),
+        safes: safes.into_iter().map(relative).collect(),
+        saturated: saturated.into_iter().map(relative).collect(),
+    }
+}
+
+fn transform_variant(variant: &Variant, transform: u8) -> Variant {
+    let apply = |(x, y): (i32, i32)| {
+        let (x, y) = if transform & 4 != 0 { (-x, y) } else { (x, y) };
+        match transform % 4 {
+            0 => (x, y),
+            1 => (-y, x),
+            2 => (-x, -y),
+            _ => (y, -x),
+        }
+    };
+    let apply_all = |positions: &[(i32, i32)]| {
+        let mut result: Vec<(i32, i32)> = positions.iter().copied().map(apply).collect();
+        result.sort();
+        result
+    };
+
+    let mut cells: Vec<((i32, i32), TemplateCell)> = variant
+        .cells
+        .iter()
+        .map(|&(pos, cell)| (apply(pos), cell))
+        .collect();
+    cells[1..].sort_by_key(|&(pos, _)| pos);
+
+    Variant {
+        cells,
+        mines: apply_all(&variant.mines),
+        safes: apply_all(&variant.safes),
+        saturated: apply_all(&variant.saturated),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::strategy::sat_solver::tests::{
+        create_solver_with_reveals, create_test_field,
+    };
+    use crate::{
+        Cell, DefinedField, MineSweeperField, SolverConfig, SolvingStrategy, create_solver,
+    };
+
+    fn solve_pattern_file(name: &str) -> Finding {
+        let field = DefinedField::from_file(&format!("generated/patterns/{}.minesweeper", name))
+            .expect("Failed to load pattern file");
+
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
+
+        solve(&solver)
+    }
+
+    #[test]
+    fn test_templates_have_deductions() {
+        for template in templates() {
+            let base = &template.variants[0];
+            assert!(
+                !base.mines.is_empty() || !base.safes.is_empty() || !base.saturated.is_empty(),
+                "Template {} deduces nothing",
+                template.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_1_2_1_deductions() {
+        let template = templates().iter().find(|t| t.name == "1-2-1").unwrap();
+        let base = &template.variants[0];
+
+        // Anchored at the 2: mines below both 1s, the cell below the 2 is safe
+        assert_eq!(base.mines, vec![(-1, 1), (1, 1)]);
+        assert_eq!(base.safes, vec![(0, 1)]);
+        assert_eq!(base.saturated, vec![(-1, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn test_variants_cover_all_orientations() {
+        let template = templates().iter().find(|t| t.name == "1-3-1").unwrap();
+        assert_eq!(template.variants.len(), 4);
+
+        let template = templates().iter().find(|t| t.name == "1-2-1").unwrap();
+        assert_eq!(template.variants.len(), 4);
+
+        let template = templates().iter().find(|t| t.name == "1-2").unwrap();
+        assert_eq!(template.variants.len(), 8);
+    }
+
+    #[test]
+    fn test_1_3_1_corner() {
+        for name in ["1-3-1-1", "1-3-1-2", "1-3-1-3"] {
+            let finding = solve_pattern_file(name);
+
+            assert!(finding.get_mine_fields().contains(&(2, 2)), "{}", name);
+            assert!(finding.get_patterns().contains(&"1-3-1"), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_1_2_2_1() {
+        let finding = solve_pattern_file("1-2-2-1");
+
+        let mut mines = finding.get_mine_fields().clone();
+        mines.sort();
+        assert_eq!(mines, vec![(1, 3), (2, 3)]);
+        assert!(finding.get_safe_fields().contains(&(0, 3)));
+        assert!(finding.get_safe_fields().contains(&(3, 3)));
+        assert!(finding.get_patterns().contains(&"1-2-2-1"));
+    }
+
+    #[test]
+    fn test_rotated_pattern() {
+        // 1-2 running down a column, wall on the left
+        let field = create_test_field(
+            "
+            ..?
+            .1?
+            .2M
+            ..M
+            ",
+        );
+        let solver = create_solver_with_reveals(
+            &field,
+            &[
+                (0, 0),
+                (1, 0),
+                (0, 1),
+                (1, 1),
+                (0, 2),
+                (1, 2),
+                (0, 3),
+                (1, 3),
+            ],
+        );
+
+        let finding = solve(&solver);
+
+        assert!(finding.get_mine_fields().contains(&(2, 3)));
+        assert!(finding.get_patterns().contains(&"1-2"));
+    }
+
+    /// Plays a pattern file with the simple strategies until the pattern shows up
+    fn find_pattern_in_file(name: &str, pattern: &str) -> (Solver, Finding) {
+        let field = DefinedField::from_file(&format!("generated/patterns/{}.minesweeper", name))
+            .expect("Failed to load pattern file");
+        let config = SolverConfig::new(vec![SolvingStrategy::Simple, SolvingStrategy::Reduction]);
+        let mut solver = crate::Solver::new(&field, &config);
+
+        while solver.step().is_some() {
+            let finding = solve(&solver);
+            if finding.get_patterns().contains(&pattern) {
+                return (solver, finding);
+            }
+        }
+        panic!("{} doesn't show the {} pattern", name, pattern);
+    }
+
+    #[test]
+    fn test_b1() {
+        let finding = solve_pattern_file("b1");
+
+        let mut mines = finding.get_mine_fields().clone();
+        mines.sort();
+        assert_eq!(mines, vec![(3, 1), (3, 2), (3, 3)]);
+        assert!(finding.get_patterns().contains(&"B1"));
+    }
+
+    #[test]
+    fn test_h2() {
+        let (solver, finding) = find_pattern_in_file("h2", "H2");
+
+        // The hole at (2,2) shares both hidden cells of the 1 above it
+        assert!(solver.get_view().is_revealed(2, 2));
+        for field in [(1, 3), (2, 3), (3, 3)] {
+            assert!(finding.get_safe_fields().contains(&field));
+        }
+    }
+
+    #[test]
+    fn test_h3() {
+        let (solver, finding) = find_pattern_in_file("h3", "H3");
+
+        // The 1s at (3,1) and (3,3) share the hidden cells (3,2) and (4,2)
+        assert!(solver.get_view().is_revealed(3, 3));
+        for field in [(4, 3), (2, 4), (3, 4), (4, 4)] {
+            assert!(finding.get_safe_fields().contains(&field));
+        }
+    }
+
+    #[test]
+    fn test_never_contradicts_solution() {
+        for name in [
+            "1-1", "1-2", "1-2-1-R", "1-2-2-1", "2-2-2", "b1", "h2", "h3",
+        ] {
+            let field =
+                DefinedField::from_file(&format!("generated/patterns/{}.minesweeper", name))
+                    .expect("Failed to load pattern file");
+            let finding = solve_pattern_file(name);
+
+            for &(x, y) in finding.get_mine_fields() {
+                assert_eq!(field.get_cell(x, y), &Cell::Mine, "{}", name);
+            }
+            for &(x, y) in finding.get_safe_fields() {
+                assert_ne!(field.get_cell(x, y), &Cell::Mine, "{}", name);
+            }
+        }
+    }
+}
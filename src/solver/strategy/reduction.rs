@@ -1,4 +1,5 @@
-use super::{Finding, Solver};
+use super::{Explanation, Finding, Rule, Solver};
+use std::collections::HashSet;
 
 /*
 Reduction strategy:
@@ -14,15 +15,17 @@ For each revealed Number cell with neighbouring hidden cells:
 pub fn solve(solver: &Solver) -> Finding {
     let mut finding = Finding::new();
 
-    for (x, y) in solver.sorted_fields() {
-        if !solver.has_informations(x, y) {
-            continue;
-        }
+    // A pair can only give something new if one of its numbers has a changed neighbourhood
+    let dirty: HashSet<(u32, u32)> = solver.dirty_frontier(1).into_iter().collect();
+
+    for (x, y) in solver.dirty_frontier(4) {
         let reduced_count = solver.get_reduced_count(x, y);
         let hidden_fields = solver.get_surrounding_unrevealed(x, y);
 
         for (new_x, new_y) in solver.surrounding_fields(x, y, Some(3)) {
-            if !solver.has_informations(new_x, new_y) {
+            if !solver.has_informations(new_x, new_y)
+                || !(dirty.contains(&(x, y)) || dirty.contains(&(new_x, new_y)))
+            {
                 continue;
             }
 
@@ -39,16 +42,40 @@ pub fn solve(solver: &Solver) -> Finding {
                 // Found two numbers which share the same unrevealed fields.
                 // Now we can check if we can solve other neighbouring fields with this extra information
 
+                let constraints = || {
+                    vec![
+                        solver.constraint_cell(new_x, new_y),
+                        solver.constraint_cell(x, y),
+                    ]
+                };
+
                 if reduced_count == reduced_count2 {
                     // Same mine count in shared area → unique fields are safe
+                    if !unique_to_second.is_empty() {
+                        finding.add_explanation(Explanation::new(
+                            Rule::SubsetReduction,
+                            constraints(),
+                            vec![],
+                            unique_to_second.clone(),
+                        ));
+                    }
                     finding.add_safe_fields(unique_to_second);
                 } else {
-                    let reduced_diff = reduced_count2 - reduced_count;
-                    let unique_count = solver.get_surrounding_unrevealed_count(new_x, new_y)
-                        - shared_fields.len() as u8;
+                    // A board from `from_visible` may give the smaller area more mines
+                    let Some(reduced_diff) = reduced_count2.checked_sub(reduced_count) else {
+                        continue;
+                    };
 
-                    if reduced_diff == unique_count {
+                    if reduced_diff as usize == unique_to_second.len() {
                         // All unique fields must be mines
+                        if !unique_to_second.is_empty() {
+                            finding.add_explanation(Explanation::new(
+                                Rule::SubsetReduction,
+                                constraints(),
+                                unique_to_second.clone(),
+                                vec![],
+                            ));
+                        }
                         finding.add_mine_fields(unique_to_second);
                     }
                 }
@@ -65,6 +92,17 @@ pub fn solve(solver: &Solver) -> Finding {
 
                 if reduced_diff == unique_to_first.len() {
                     // All unique fields of first cell must be mines
+                    if !unique_to_first.is_empty() {
+                        finding.add_explanation(Explanation::new(
+                            Rule::SubsetReduction,
+                            vec![
+                                solver.constraint_cell(x, y),
+                                solver.constraint_cell(new_x, new_y),
+                            ],
+                            unique_to_first.clone(),
+                            vec![],
+                        ));
+                    }
                     finding.add_mine_fields(unique_to_first);
                 }
             }
@@ -77,15 +115,15 @@ pub fn solve(solver: &Solver) -> Finding {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::DefinedField;
+    use crate::{DefinedField, VisibleCell, create_solver};
 
     #[test]
     fn test_1_1_pattern() {
         let field = DefinedField::from_file("generated/patterns/1-1.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -101,8 +139,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/1-2.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -118,8 +156,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/1-2-1-R.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -135,8 +173,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/1-2-2-1.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -151,8 +189,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/1-3-1-1.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -167,8 +205,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/1-3-1-2.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -183,8 +221,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/1-3-1-3.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -199,8 +237,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/2-2-2.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -215,8 +253,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/b1.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -231,8 +269,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/h2.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -247,8 +285,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/h3.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -257,4 +295,18 @@ mod tests {
             "Should find some certain fields in h3 pattern"
         );
     }
+
+    #[test]
+    fn test_contradicting_numbers() {
+        // The 2 needs both cells below it, the 1 next to it sees the same cells
+        let cells = vec![
+            vec![VisibleCell::Revealed(2), VisibleCell::Hidden],
+            vec![VisibleCell::Revealed(1), VisibleCell::Hidden],
+            vec![VisibleCell::Revealed(1), VisibleCell::Hidden],
+        ];
+        let solver = Solver::from_visible(&cells, 2, &crate::SolverConfig::default()).unwrap();
+
+        // The numbers can't all be right, the strategy must not underflow on them
+        solve(&solver);
+    }
 }
@@ -3,17 +3,16 @@ use std::collections::{HashMap, HashSet};
 
 /// Find all independent components of border fields
 /// Each component contains fields that are mutually dependent through shared constraints
-/// Fields in different components have no shared constraints and can be solved independently
+/// Fields in different components have no shared constraints and can be solved independently.
+/// Components and their fields are ordered row by row, so the solving steps are reproducible
 pub fn find_independent_components(solver: &Solver) -> Vec<Vec<(u32, u32)>> {
     // Step 1: Collect all border fields (unrevealed fields adjacent to revealed numbers)
     let mut all_border_fields: HashSet<(u32, u32)> = HashSet::new();
 
-    for (x, y) in solver.sorted_fields() {
-        if solver.has_informations(x, y) {
-            let unrevealed = solver.get_surrounding_unrevealed(x, y);
-            for field in unrevealed {
-                all_border_fields.insert(field);
-            }
+    for (x, y) in solver.frontier() {
+        let unrevealed = solver.get_surrounding_unrevealed(x, y);
+        for field in unrevealed {
+            all_border_fields.insert(field);
         }
     }
 
@@ -26,11 +25,7 @@ pub fn find_independent_components(solver: &Solver) -> Vec<Vec<(u32, u32)>> {
     // This ensures all fields affected by the same constraint stay in the same component
     let mut adjacency: HashMap<(u32, u32), HashSet<(u32, u32)>> = HashMap::new();
 
-    for (x, y) in solver.sorted_fields() {
-        if !solver.has_informations(x, y) {
-            continue;
-        }
-
+    for (x, y) in solver.frontier() {
         // Get all unrevealed neighbors that are border fields
         let unrevealed = solver.get_surrounding_unrevealed(x, y);
         let constraint_fields: Vec<_> = unrevealed
@@ -54,10 +49,14 @@ pub fn find_independent_components(solver: &Solver) -> Vec<Vec<(u32, u32)>> {
     }
 
     // Step 3: Find connected components using Depth-First Search (DFS)
+    // Starting row by row, each component starts at its first field
+    let mut start_fields: Vec<(u32, u32)> = all_border_fields.into_iter().collect();
+    start_fields.sort_by_key(|&(x, y)| (y, x));
+
     let mut components: Vec<Vec<(u32, u32)>> = Vec::new();
     let mut visited: HashSet<(u32, u32)> = HashSet::new();
 
-    for &start_field in &all_border_fields {
+    for start_field in start_fields {
         if visited.contains(&start_field) {
             continue;
         }
@@ -83,6 +82,7 @@ pub fn find_independent_components(solver: &Solver) -> Vec<Vec<(u32, u32)>> {
             }
         }
 
+        component.sort_by_key(|&(x, y)| (y, x));
         components.push(component);
     }
 
@@ -17,11 +17,7 @@ pub fn build_constraints(solver: &Solver, component: &[(u32, u32)]) -> Vec<Const
     let component_set: std::collections::HashSet<_> = component.iter().copied().collect();
 
     // Iterate through all revealed cells with information
-    for (x, y) in solver.sorted_fields() {
-        if !solver.has_informations(x, y) {
-            continue;
-        }
-
+    for (x, y) in solver.frontier() {
         // Get unrevealed neighbors that are in this component
         let unrevealed = solver.get_surrounding_unrevealed(x, y);
         let constraint_fields: Vec<_> = unrevealed
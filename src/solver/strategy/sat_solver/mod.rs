@@ -1,13 +1,14 @@
 mod components;
 mod constraint_builder;
 mod sat_solving;
+mod solutions;
 mod solve;
 
 #[cfg(test)]
-mod tests;
+pub(super) mod tests;
 
-#[cfg(test)]
-use components::find_independent_components;
-
-use super::{Finding, Solver};
+use super::{Explanation, Finding, GiveUpReason, Rule, Solver};
+pub(crate) use components::find_independent_components;
+pub(crate) use constraint_builder::{Constraint, build_constraints};
+pub(crate) use solutions::{ComponentSolutions, enumerate_solutions, enumerate_solutions_within};
 pub use solve::solve;
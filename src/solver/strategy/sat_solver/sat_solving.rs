@@ -1,220 +1,563 @@
 /*
-Constraint propagation + backtracking solver for minesweeper components.
-
-For each cell in a component, determines if it is 100% mine, 100% safe,
-or undetermined. Uses two SAT-style feasibility queries per cell:
-  - Force cell = mine, check if any valid complete assignment exists
-  - Force cell = safe, check if any valid complete assignment exists
-If only one succeeds, the cell is determined.
-
-Each query uses constraint propagation (arc consistency) to prune the
-search tree, with backtracking that stops at the first valid solution.
-Rayon parallelizes the per-cell queries across threads.
+Solution graph search for minesweeper components.
+
+Instead of asking "can this cell be a mine / be safe" twice per cell, all consistent
+assignments of a component are explored in one search and stored as a graph:
+- Cells are decided in a fixed order, preferring neighbours of already ordered cells,
+  so only a few constraints are open (partially assigned) at any point of the search.
+- Every cell watches the constraints it is part of. Assigning a cell only updates the
+  counters (assigned mines, unassigned cells) of its watched constraints, and only those
+  constraints are checked for conflicts or forced cells (all unknowns mines / all safe).
+  Assignments are recorded on a trail and undone in place, nothing gets cloned per branch.
+- After propagation the remaining subproblem only depends on the residual mine counts of the
+  open constraints and the state of the undecided cells. Subproblems are cached by this state,
+  so equal states reached by different branches are solved once. A cached state without
+  solutions is a learned conflict which prunes every later branch reaching it.
+
+Each graph node stores how many completions it has per number of mines. A forward pass over
+the graph then yields, for every cell, how many solutions make it a mine or keep it safe.
+
+Every explored subproblem counts as a search node against the solver budget. Once the budget runs out
+the search stops and the component gives up, a partial graph would count too few solutions.
 */
 
 use super::constraint_builder::Constraint;
-use crate::solver::Finding;
-use rayon::prelude::*;
-use std::collections::HashMap;
+use crate::solver::{Finding, GiveUpReason, SearchCounters, SolverBudget};
+use std::collections::{HashMap, VecDeque};
 
 pub fn solve_component(
     component: &[(u32, u32)],
     constraints: &[Constraint],
     remaining_mines: u32,
-) -> Finding {
+    budget: &SolverBudget,
+    counters: &SearchCounters,
+) -> Result<Finding, GiveUpReason> {
     let mut finding = Finding::new();
 
     if component.is_empty() || constraints.is_empty() {
-        return finding;
-    }
-
-    let field_indices: HashMap<(u32, u32), usize> = component
-        .iter()
-        .enumerate()
-        .map(|(i, &pos)| (pos, i))
-        .collect();
-
-    let results: Vec<(usize, Option<bool>)> = (0..component.len())
-        .into_par_iter()
-        .map(|idx| {
-            let can_be_mine = can_be_value(
-                idx,
-                true,
-                component.len(),
-                constraints,
-                &field_indices,
-                remaining_mines,
-            );
-            let can_be_safe = can_be_value(
-                idx,
-                false,
-                component.len(),
-                constraints,
-                &field_indices,
-                remaining_mines,
-            );
-
-            let determination = match (can_be_mine, can_be_safe) {
-                (true, false) => Some(true),
-                (false, true) => Some(false),
-                _ => None,
-            };
+        return Ok(finding);
+    }
+
+    let graph = SolutionGraph::build_within(component, constraints, budget, counters)?;
+    finding.add_searched_component(component.len(), graph.node_count());
+    let (mine_totals, safe_totals) = graph.cell_totals(remaining_mines);
+
+    for (idx, &field) in component.iter().enumerate() {
+        match (mine_totals[idx] > 0.0, safe_totals[idx] > 0.0) {
+            (true, false) => finding.add_mine_field(field),
+            (false, true) => finding.add_safe_field(field),
+            _ => {}
+        }
+    }
+
+    Ok(finding)
+}
+
+/// Explored search space of a component.
+/// Cells are referred to by their index in the component.
+pub(super) struct SolutionGraph {
+    size: usize,
+    nodes: Vec<Node>,
+    /// Cells assigned by propagation before the first decision
+    root_assignments: Vec<(usize, bool)>,
+    /// Mines among the cells before the cursor of the root node
+    root_mines: usize,
+    /// None if the constraints can't be satisfied at all
+    root: Option<usize>,
+}
+
+struct Node {
+    /// Position of the next undecided cell in the search order
+    cursor: usize,
+    /// `completions[j]`: solutions of the subproblem placing `j` mines from the cursor onwards
+    completions: Vec<f64>,
+    edges: Vec<Edge>,
+}
+
+struct Edge {
+    target: usize,
+    /// Cells assigned by the decision and its propagation
+    assignments: Vec<(usize, bool)>,
+    /// Mines between the cursor of the source and the cursor of the target
+    mines: usize,
+}
+
+impl SolutionGraph {
+    #[cfg(test)]
+    pub(super) fn build(component: &[(u32, u32)], constraints: &[Constraint]) -> Self {
+        let unlimited = SolverBudget::default();
+        match Self::build_within(
+            component,
+            constraints,
+            &unlimited,
+            &SearchCounters::default(),
+        ) {
+            Ok(graph) => graph,
+            Err(_) => unreachable!("an unlimited search never gives up"),
+        }
+    }
+
+    /// Builds the graph, giving up once the search exceeds the budget.
+    /// The effort of the search is added to `counters`, also if it gives up.
+    pub(super) fn build_within(
+        component: &[(u32, u32)],
+        constraints: &[Constraint],
+        budget: &SolverBudget,
+        counters: &SearchCounters,
+    ) -> Result<Self, GiveUpReason> {
+        let mut search = Search::new(component, constraints, budget);
+        let graph = Self::search(&mut search, component);
+        counters.record(component.len(), search.visits, search.propagations);
+        graph
+    }
+
+    fn search(search: &mut Search, component: &[(u32, u32)]) -> Result<Self, GiveUpReason> {
+        for c in 0..search.constraints.len() {
+            search.queue.push_back(c);
+        }
+
+        if !search.propagate() {
+            return Ok(SolutionGraph {
+                size: component.len(),
+                nodes: Vec::new(),
+                root_assignments: Vec::new(),
+                root_mines: 0,
+                root: None,
+            });
+        }
+
+        let root_assignments = search.trail_assignments(0);
+        let cursor = search.next_cursor(0);
+        let root_mines = search.mines_between(0, cursor);
+        let root = search.explore(cursor);
+        if let Some(reason) = search.gave_up {
+            return Err(reason);
+        }
 
-            (idx, determination)
+        Ok(SolutionGraph {
+            size: component.len(),
+            nodes: std::mem::take(&mut search.nodes),
+            root_assignments,
+            root_mines,
+            root: Some(root),
         })
-        .collect();
+    }
 
-    for (idx, determination) in results {
-        if let Some(is_mine) = determination {
-            if is_mine {
-                finding.add_mine_field(component[idx]);
-            } else {
-                finding.add_safe_field(component[idx]);
+    pub(super) fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// `result[k]`: number of solutions placing exactly `k` mines
+    pub(super) fn solution_counts(&self) -> Vec<f64> {
+        let mut counts = vec![0.0; self.size + 1];
+        if let Some(root) = self.root {
+            add_shifted(&mut counts, &self.nodes[root].completions, self.root_mines);
+        }
+        counts
+    }
+
+    /// Per cell and per number of mines: how many solutions make the cell a mine / keep it safe.
+    /// Both are counted directly, so a cell is only forced if the other count is exactly zero.
+    pub(super) fn cell_counts(&self) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+        let mut mine_counts = vec![vec![0.0; self.size]; self.size + 1];
+        let mut safe_counts = vec![vec![0.0; self.size]; self.size + 1];
+
+        let Some(root) = self.root else {
+            return (mine_counts, safe_counts);
+        };
+
+        let mut add = |assignments: &[(usize, bool)], solutions: &[f64]| {
+            for &(cell, is_mine) in assignments {
+                let counts = if is_mine {
+                    &mut mine_counts
+                } else {
+                    &mut safe_counts
+                };
+                for (k, &count) in solutions.iter().enumerate() {
+                    if count > 0.0 {
+                        counts[k][cell] += count;
+                    }
+                }
+            }
+        };
+
+        let mut root_solutions = vec![0.0; self.size + 1];
+        add_shifted(
+            &mut root_solutions,
+            &self.nodes[root].completions,
+            self.root_mines,
+        );
+        add(&self.root_assignments, &root_solutions);
+
+        // prefixes[n][m]: partial assignments reaching node n with m mines before its cursor.
+        // Edges always move the cursor forward, so sorting by cursor is a topological order.
+        let mut prefixes: Vec<Vec<f64>> = vec![Vec::new(); self.nodes.len()];
+        prefixes[root] = vec![0.0; self.root_mines + 1];
+        prefixes[root][self.root_mines] = 1.0;
+
+        let mut order: Vec<usize> = (0..self.nodes.len()).collect();
+        order.sort_by_key(|&n| self.nodes[n].cursor);
+
+        for n in order {
+            if prefixes[n].is_empty() {
+                continue;
+            }
+            let prefix = std::mem::take(&mut prefixes[n]);
+
+            for edge in &self.nodes[n].edges {
+                let target = &mut prefixes[edge.target];
+                add_shifted(target, &prefix, edge.mines);
+
+                let mut solutions = vec![0.0; self.size + 1];
+                for (m, &before) in prefix.iter().enumerate() {
+                    if before == 0.0 {
+                        continue;
+                    }
+                    for (j, &after) in self.nodes[edge.target].completions.iter().enumerate() {
+                        solutions[m + edge.mines + j] += before * after;
+                    }
+                }
+                add(&edge.assignments, &solutions);
             }
         }
+
+        (mine_counts, safe_counts)
     }
 
-    finding
-}
+    /// Summed mine / safe counts per cell over all solutions using at most `max_mines` mines
+    fn cell_totals(&self, max_mines: u32) -> (Vec<f64>, Vec<f64>) {
+        let (mine_counts, safe_counts) = self.cell_counts();
+        let limit = self.size.min(max_mines as usize);
 
-fn can_be_value(
-    forced_index: usize,
-    forced_value: bool,
-    component_len: usize,
-    constraints: &[Constraint],
-    field_indices: &HashMap<(u32, u32), usize>,
-    remaining_mines: u32,
-) -> bool {
-    let mut assignment = vec![None; component_len];
-    assignment[forced_index] = Some(forced_value);
+        let total = |counts: &[Vec<f64>]| -> Vec<f64> {
+            (0..self.size)
+                .map(|cell| counts[..=limit].iter().map(|row| row[cell]).sum())
+                .collect()
+        };
 
-    if !propagate(&mut assignment, constraints, field_indices, remaining_mines) {
-        return false;
+        (total(&mine_counts), total(&safe_counts))
     }
+}
 
-    backtrack_search(&mut assignment, constraints, field_indices, remaining_mines)
+/// Adds `source` shifted by `offset` onto `target`, growing `target` if needed
+fn add_shifted(target: &mut Vec<f64>, source: &[f64], offset: usize) {
+    if target.len() < source.len() + offset {
+        target.resize(source.len() + offset, 0.0);
+    }
+    for (i, &value) in source.iter().enumerate() {
+        target[i + offset] += value;
+    }
 }
 
-fn propagate(
-    assignment: &mut [Option<bool>],
-    constraints: &[Constraint],
-    field_indices: &HashMap<(u32, u32), usize>,
-    remaining_mines: u32,
-) -> bool {
-    let mut changed = true;
+struct Search<'a> {
+    constraints: &'a [Constraint],
+    /// Component indices of the cells of each constraint
+    constraint_cells: Vec<Vec<usize>>,
+    /// Constraints watched by each cell
+    watches: Vec<Vec<usize>>,
+    /// Search order: `order[position]` is a cell, `position[cell]` its place in the order
+    order: Vec<usize>,
+    position: Vec<usize>,
+    /// `open[cursor]`: constraints with at least one cell at or after the cursor
+    open: Vec<Vec<usize>>,
+    constraint_mines: Vec<u32>,
+    constraint_unknowns: Vec<u32>,
+    assignment: Vec<Option<bool>>,
+    trail: Vec<usize>,
+    queue: VecDeque<usize>,
+    cache: HashMap<Vec<u8>, usize>,
+    nodes: Vec<Node>,
+    budget: &'a SolverBudget,
+    /// Explored subproblems, including those answered from the cache
+    visits: usize,
+    /// Cells forced by propagation
+    propagations: usize,
+    gave_up: Option<GiveUpReason>,
+}
 
-    while changed {
-        changed = false;
+impl<'a> Search<'a> {
+    fn new(
+        component: &[(u32, u32)],
+        constraints: &'a [Constraint],
+        budget: &'a SolverBudget,
+    ) -> Self {
+        let size = component.len();
+        let field_indices: HashMap<(u32, u32), usize> = component
+            .iter()
+            .enumerate()
+            .map(|(i, &pos)| (pos, i))
+            .collect();
+
+        let mut constraint_cells = Vec::with_capacity(constraints.len());
+        let mut watches = vec![Vec::new(); size];
+        for (c, constraint) in constraints.iter().enumerate() {
+            let cells: Vec<usize> = constraint
+                .fields
+                .iter()
+                .filter_map(|pos| field_indices.get(pos).copied())
+                .collect();
+            for &cell in &cells {
+                watches[cell].push(c);
+            }
+            constraint_cells.push(cells);
+        }
 
-        // Global mine count check
-        let total_mines = assignment.iter().filter(|a| **a == Some(true)).count() as u32;
-        if total_mines > remaining_mines {
-            return false;
+        let order = search_order(size, &constraint_cells, &watches);
+        let mut position = vec![0; size];
+        for (p, &cell) in order.iter().enumerate() {
+            position[cell] = p;
         }
 
-        let total_unknowns = assignment.iter().filter(|a| a.is_none()).count() as u32;
-        if total_mines == remaining_mines && total_unknowns > 0 {
-            // All remaining unknowns must be safe
-            for cell in assignment.iter_mut() {
-                if cell.is_none() {
-                    *cell = Some(false);
-                    changed = true;
+        let mut open = vec![Vec::new(); size + 1];
+        for (c, cells) in constraint_cells.iter().enumerate() {
+            if let Some(last) = cells.iter().map(|&cell| position[cell]).max() {
+                for cursor in open.iter_mut().take(last + 1) {
+                    cursor.push(c);
                 }
             }
         }
 
-        for constraint in constraints {
-            let mut mines = 0u32;
-            let mut unknowns = Vec::new();
+        let constraint_unknowns = constraint_cells
+            .iter()
+            .map(|cells| cells.len() as u32)
+            .collect();
+
+        Search {
+            constraints,
+            constraint_cells,
+            watches,
+            order,
+            position,
+            open,
+            constraint_mines: vec![0; constraints.len()],
+            constraint_unknowns,
+            assignment: vec![None; size],
+            trail: Vec::new(),
+            queue: VecDeque::new(),
+            cache: HashMap::new(),
+            nodes: Vec::new(),
+            budget,
+            visits: 0,
+            propagations: 0,
+            gave_up: None,
+        }
+    }
+
+    /// Explores the subproblem starting at `cursor` and returns its node
+    fn explore(&mut self, cursor: usize) -> usize {
+        self.visits += 1;
+        if let Err(reason) = self.budget.check_search(self.visits) {
+            self.gave_up.get_or_insert(reason);
+        }
+        if self.gave_up.is_some() {
+            return self.dead_end(cursor);
+        }
+
+        let key = self.state_key(cursor);
+        if let Some(&node) = self.cache.get(&key) {
+            return node;
+        }
 
-            for &field_pos in &constraint.fields {
-                if let Some(&idx) = field_indices.get(&field_pos) {
-                    match assignment[idx] {
-                        Some(true) => mines += 1,
-                        Some(false) => {}
-                        None => unknowns.push(idx),
+        let mut node = Node {
+            cursor,
+            completions: Vec::new(),
+            edges: Vec::new(),
+        };
+
+        if cursor == self.order.len() {
+            node.completions.push(1.0);
+        } else {
+            let cell = self.order[cursor];
+
+            for value in [false, true] {
+                let mark = self.trail.len();
+                self.assign(cell, value);
+
+                if self.propagate() {
+                    let next = self.next_cursor(cursor);
+                    let mines = self.mines_between(cursor, next);
+                    let assignments = self.trail_assignments(mark);
+                    let target = self.explore(next);
+
+                    if !self.nodes[target].completions.is_empty() {
+                        add_shifted(
+                            &mut node.completions,
+                            &self.nodes[target].completions,
+                            mines,
+                        );
+                        node.edges.push(Edge {
+                            target,
+                            assignments,
+                            mines,
+                        });
                     }
                 }
+
+                self.undo(mark);
             }
+        }
 
-            // Contradiction: too many mines
-            if mines > constraint.mine_count {
-                return false;
+        let id = self.nodes.len();
+        self.nodes.push(node);
+        // An aborted search leaves the node incomplete
+        if self.gave_up.is_none() {
+            self.cache.insert(key, id);
+        }
+        id
+    }
+
+    /// Node without completions, returned once the search gave up
+    fn dead_end(&mut self, cursor: usize) -> usize {
+        self.nodes.push(Node {
+            cursor,
+            completions: Vec::new(),
+            edges: Vec::new(),
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Encodes everything the subproblem at `cursor` depends on:
+    /// the state of all cells from the cursor onwards and the mines still
+    /// needed by the open constraints, not counting the cells from the cursor onwards.
+    fn state_key(&self, cursor: usize) -> Vec<u8> {
+        let mut key = Vec::with_capacity(4 + self.order.len() - cursor + self.open[cursor].len());
+        key.extend_from_slice(&(cursor as u32).to_le_bytes());
+
+        for &cell in &self.order[cursor..] {
+            key.push(match self.assignment[cell] {
+                None => 0,
+                Some(false) => 1,
+                Some(true) => 2,
+            });
+        }
+
+        for &c in &self.open[cursor] {
+            let mines_before = self.constraint_cells[c]
+                .iter()
+                .filter(|&&cell| {
+                    self.position[cell] < cursor && self.assignment[cell] == Some(true)
+                })
+                .count() as u32;
+            key.push((self.constraints[c].mine_count - mines_before) as u8);
+        }
+
+        key
+    }
+
+    fn assign(&mut self, cell: usize, value: bool) {
+        self.assignment[cell] = Some(value);
+        self.trail.push(cell);
+
+        for &c in &self.watches[cell] {
+            self.constraint_unknowns[c] -= 1;
+            if value {
+                self.constraint_mines[c] += 1;
             }
+            self.queue.push_back(c);
+        }
+    }
+
+    /// Checks all queued constraints and assigns cells they force.
+    /// Returns false on a contradiction.
+    fn propagate(&mut self) -> bool {
+        while let Some(c) = self.queue.pop_front() {
+            let mines = self.constraint_mines[c];
+            let unknowns = self.constraint_unknowns[c];
+            let needed = self.constraints[c].mine_count;
 
-            let remaining_needed = constraint.mine_count - mines;
-            if remaining_needed > unknowns.len() as u32 {
+            if mines > needed || mines + unknowns < needed {
+                self.queue.clear();
                 return false;
             }
 
-            // All unknowns must be mines
-            if remaining_needed == unknowns.len() as u32 && !unknowns.is_empty() {
-                for idx in unknowns {
-                    assignment[idx] = Some(true);
-                    changed = true;
-                }
+            if unknowns == 0 {
+                continue;
             }
-            // No more mines needed — all unknowns are safe
-            else if remaining_needed == 0 && !unknowns.is_empty() {
-                for idx in unknowns {
-                    assignment[idx] = Some(false);
-                    changed = true;
+
+            let value = if mines == needed {
+                false
+            } else if mines + unknowns == needed {
+                true
+            } else {
+                continue;
+            };
+
+            for i in 0..self.constraint_cells[c].len() {
+                let cell = self.constraint_cells[c][i];
+                if self.assignment[cell].is_none() {
+                    self.assign(cell, value);
+                    self.propagations += 1;
                 }
             }
         }
-    }
-
-    true
-}
 
-fn backtrack_search(
-    assignment: &mut Vec<Option<bool>>,
-    constraints: &[Constraint],
-    field_indices: &HashMap<(u32, u32), usize>,
-    remaining_mines: u32,
-) -> bool {
-    let position = match assignment.iter().position(|a| a.is_none()) {
-        Some(p) => p,
-        None => return is_valid_solution(assignment, constraints, field_indices, remaining_mines),
-    };
+        true
+    }
 
-    for value in [false, true] {
-        let mut branch = assignment.clone();
-        branch[position] = Some(value);
+    fn undo(&mut self, mark: usize) {
+        while self.trail.len() > mark {
+            let cell = self.trail.pop().unwrap();
+            let value = self.assignment[cell].take().unwrap();
 
-        if propagate(&mut branch, constraints, field_indices, remaining_mines)
-            && backtrack_search(&mut branch, constraints, field_indices, remaining_mines)
-        {
-            return true;
+            for &c in &self.watches[cell] {
+                self.constraint_unknowns[c] += 1;
+                if value {
+                    self.constraint_mines[c] -= 1;
+                }
+            }
         }
     }
 
-    false
-}
+    fn trail_assignments(&self, mark: usize) -> Vec<(usize, bool)> {
+        self.trail[mark..]
+            .iter()
+            .map(|&cell| (cell, self.assignment[cell].unwrap()))
+            .collect()
+    }
 
-fn is_valid_solution(
-    assignment: &[Option<bool>],
-    constraints: &[Constraint],
-    field_indices: &HashMap<(u32, u32), usize>,
-    remaining_mines: u32,
-) -> bool {
-    let mine_count = assignment.iter().filter(|a| **a == Some(true)).count() as u32;
-    if mine_count > remaining_mines {
-        return false;
+    fn next_cursor(&self, cursor: usize) -> usize {
+        (cursor..self.order.len())
+            .find(|&p| self.assignment[self.order[p]].is_none())
+            .unwrap_or(self.order.len())
     }
 
-    for constraint in constraints {
-        let mines: u32 = constraint
-            .fields
+    fn mines_between(&self, from: usize, to: usize) -> usize {
+        self.order[from..to]
             .iter()
-            .filter_map(|pos| field_indices.get(pos))
-            .filter(|&&idx| assignment[idx] == Some(true))
-            .count() as u32;
+            .filter(|&&cell| self.assignment[cell] == Some(true))
+            .count()
+    }
+}
 
-        if mines != constraint.mine_count {
-            return false;
+/// Breadth-first order over shared constraints, so neighbouring cells are decided together
+/// and constraints get closed soon after they are opened.
+fn search_order(
+    size: usize,
+    constraint_cells: &[Vec<usize>],
+    watches: &[Vec<usize>],
+) -> Vec<usize> {
+    let mut order = Vec::with_capacity(size);
+    let mut visited = vec![false; size];
+
+    for start in 0..size {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(cell) = queue.pop_front() {
+            order.push(cell);
+            for &c in &watches[cell] {
+                for &neighbour in &constraint_cells[c] {
+                    if !visited[neighbour] {
+                        visited[neighbour] = true;
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
         }
     }
 
-    true
+    order
 }
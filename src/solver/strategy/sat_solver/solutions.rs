@@ -0,0 +1,61 @@
+/*
+Counting all valid assignments of a component.
+
+Instead of answering "can this cell be a mine", every consistent assignment is counted,
+grouped by the number of mines it places. The counts allow exact probabilities and
+reasoning about the global mine count. The search itself lives in `sat_solving`.
+*/
+
+use super::constraint_builder::Constraint;
+use super::sat_solving::SolutionGraph;
+use crate::solver::{GiveUpReason, SearchCounters, SolverBudget};
+
+/// All valid assignments of a component, grouped by the number of mines they place.
+#[derive(Debug, Clone)]
+pub struct ComponentSolutions {
+    /// `solution_counts[k]`: number of assignments placing exactly `k` mines
+    pub solution_counts: Vec<f64>,
+    /// `mine_counts[k][i]`: number of assignments with `k` mines where cell `i` is a mine
+    pub mine_counts: Vec<Vec<f64>>,
+    /// `safe_counts[k][i]`: number of assignments with `k` mines where cell `i` is safe
+    pub safe_counts: Vec<Vec<f64>>,
+}
+
+pub fn enumerate_solutions(
+    component: &[(u32, u32)],
+    constraints: &[Constraint],
+    max_mines: u32,
+) -> ComponentSolutions {
+    let unlimited = SolverBudget::default();
+    let counters = SearchCounters::default();
+    match enumerate_solutions_within(component, constraints, max_mines, &unlimited, &counters) {
+        Ok(solutions) => solutions,
+        Err(_) => unreachable!("an unlimited search never gives up"),
+    }
+}
+
+/// Like [`enumerate_solutions`], giving up once the search exceeds the budget.
+/// The effort of the search is added to `counters`.
+pub fn enumerate_solutions_within(
+    component: &[(u32, u32)],
+    constraints: &[Constraint],
+    max_mines: u32,
+    budget: &SolverBudget,
+    counters: &SearchCounters,
+) -> Result<ComponentSolutions, GiveUpReason> {
+    let graph = SolutionGraph::build_within(component, constraints, budget, counters)?;
+
+    let max_k = component.len().min(max_mines as usize);
+    let mut solution_counts = graph.solution_counts();
+    let (mut mine_counts, mut safe_counts) = graph.cell_counts();
+
+    solution_counts.truncate(max_k + 1);
+    mine_counts.truncate(max_k + 1);
+    safe_counts.truncate(max_k + 1);
+
+    Ok(ComponentSolutions {
+        solution_counts,
+        mine_counts,
+        safe_counts,
+    })
+}
@@ -1,8 +1,9 @@
 use super::components::find_independent_components;
 use super::constraint_builder::build_constraints;
 use super::sat_solving::solve_component;
-use super::{Finding, Solver};
+use super::{Explanation, Finding, GiveUpReason, Rule, Solver};
 use log::trace;
+use rayon::prelude::*;
 
 pub fn solve(solver: &Solver) -> Finding {
     let mut finding = Finding::new();
@@ -11,27 +12,65 @@ pub fn solve(solver: &Solver) -> Finding {
 
     trace!("Found {} independent component(s)", components.len());
 
-    for (i, component) in components.iter().enumerate() {
-        trace!("  Component {}: {} fields", i + 1, component.len());
+    let remaining_mines = solver.get_remaining_mines();
+    let budget = solver.config.get_budget();
 
-        let constraints = build_constraints(solver, &component);
+    // Every component is searched once, so the components are solved in parallel
+    let component_findings: Vec<Result<Finding, GiveUpReason>> = components
+        .par_iter()
+        .enumerate()
+        .map(|(i, component)| {
+            let constraints = build_constraints(solver, component);
 
-        trace!(
-            "  Component {} has {} constraints",
-            i + 1,
-            constraints.len()
-        );
+            trace!(
+                "  Component {}: {} fields, {} constraints",
+                i + 1,
+                component.len(),
+                constraints.len()
+            );
 
-        let remaining_mines = solver.get_remaining_mines();
-        let component_finding = solve_component(&component, &constraints, remaining_mines);
+            let component_finding = solve_component(
+                component,
+                &constraints,
+                remaining_mines,
+                budget,
+                &solver.search_counters,
+            );
 
-        trace!(
-            "  Component {} found {} mines, {} safe",
-            i + 1,
-            component_finding.get_mine_fields().len(),
-            component_finding.get_safe_fields().len()
-        );
+            match &component_finding {
+                Ok(component_finding) => trace!(
+                    "  Component {} found {} mines, {} safe",
+                    i + 1,
+                    component_finding.get_mine_fields().len(),
+                    component_finding.get_safe_fields().len()
+                ),
+                Err(reason) => trace!("  Component {} gave up: {}", i + 1, reason),
+            }
 
+            component_finding
+        })
+        .collect();
+
+    for (component, component_finding) in components.iter().zip(component_findings) {
+        // The other components are still solved
+        let component_finding = match component_finding {
+            Ok(component_finding) => component_finding,
+            Err(reason) => {
+                finding.give_up(reason);
+                continue;
+            }
+        };
+        for &(size, search_nodes) in component_finding.get_searched_components() {
+            finding.add_searched_component(size, search_nodes);
+        }
+        if component_finding.success() {
+            finding.add_explanation(Explanation::new(
+                Rule::CaseSplit,
+                solver.constraint_cells_of(component),
+                component_finding.get_mine_fields().clone(),
+                component_finding.get_safe_fields().clone(),
+            ));
+        }
         finding.add_mine_fields(component_finding.get_mine_fields().clone());
         finding.add_safe_fields(component_finding.get_safe_fields().clone());
     }
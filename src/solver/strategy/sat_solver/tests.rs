@@ -1,6 +1,6 @@
 use super::find_independent_components;
 use crate::solver::{Solver, cell_state::CellState};
-use crate::{DefinedField, MineSweeperField, Mines};
+use crate::{DefinedField, MineSweeperField, Mines, create_solver};
 
 /// Helper to create a test field from a string pattern
 /// Format:
@@ -34,12 +34,12 @@ pub fn create_test_field(pattern: &str) -> DefinedField {
 /// Helper to create a solver and directly set specific cells as revealed
 /// This avoids the cascade reveal behavior of reveal_cell()
 pub fn create_solver_with_reveals(field: &impl MineSweeperField, reveals: &[(u32, u32)]) -> Solver {
-    let mut solver = Solver::new(field);
+    let mut solver = create_solver(field);
 
     // Directly set cells as revealed without triggering cascade
     for &(x, y) in reveals {
         let cell = field.get_cell(x, y);
-        solver.state[x as usize][y as usize] = CellState::Revealed(cell.clone());
+        solver.set_state(x, y, CellState::Revealed(cell.clone()));
     }
 
     solver
@@ -230,9 +230,9 @@ mod component_tests {
         ";
 
         let field = create_test_field(pattern);
-        let mut solver = Solver::new(&field);
-        let _ = solver.reveal_cell(0, 0, &mut vec![vec![]], 0); // Reveal the safe cell
-        solver.flag_cell(1, 0); // Flag the mine
+        let mut solver = create_solver(&field);
+        let _ = solver.reveal_cell(0, 0, &mut vec![vec![]]); // Reveal the safe cell
+        solver.flag_cell(1, 0).unwrap(); // Flag the mine
 
         let components = find_independent_components(&solver);
 
@@ -401,7 +401,7 @@ mod constraint_tests {
 
         let field = create_test_field(pattern);
         let mut solver = create_solver_with_reveals(&field, &[(0, 1)]);
-        solver.flag_cell(0, 0); // Flag one mine
+        solver.flag_cell(0, 0).unwrap(); // Flag one mine
 
         let component = vec![(1, 0)];
         let constraints = build_constraints(&solver, &component);
@@ -428,8 +428,8 @@ mod solver_tests {
         let field = DefinedField::from_file("generated/patterns/1-1.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = super::super::solve(&solver);
 
@@ -439,13 +439,32 @@ mod solver_tests {
         );
     }
 
+    #[test]
+    fn test_records_searched_components() {
+        let field = DefinedField::from_file("generated/patterns/1-1.minesweeper")
+            .expect("Failed to load pattern file");
+
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
+
+        let finding = super::super::solve(&solver);
+        let components = find_independent_components(&solver);
+
+        let searched = finding.get_searched_components();
+        assert_eq!(searched.len(), components.len());
+        for (&(size, nodes), component) in searched.iter().zip(&components) {
+            assert_eq!(size, component.len());
+            assert!(nodes > 0);
+        }
+    }
+
     #[test]
     fn test_pattern_1_2() {
         let field = DefinedField::from_file("generated/patterns/1-2.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = super::super::solve(&solver);
 
@@ -460,8 +479,8 @@ mod solver_tests {
         let field = DefinedField::from_file("generated/patterns/1-2-1-R.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = super::super::solve(&solver);
 
@@ -476,8 +495,8 @@ mod solver_tests {
         let field = DefinedField::from_file("generated/patterns/1-2-2-1.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = super::super::solve(&solver);
 
@@ -492,8 +511,8 @@ mod solver_tests {
         let field = DefinedField::from_file("generated/patterns/1-3-1-1.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = super::super::solve(&solver);
 
@@ -508,8 +527,8 @@ mod solver_tests {
         let field = DefinedField::from_file("generated/patterns/1-3-1-2.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = super::super::solve(&solver);
 
@@ -524,8 +543,8 @@ mod solver_tests {
         let field = DefinedField::from_file("generated/patterns/1-3-1-3.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = super::super::solve(&solver);
 
@@ -540,8 +559,8 @@ mod solver_tests {
         let field = DefinedField::from_file("generated/patterns/2-2-2.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = super::super::solve(&solver);
 
@@ -556,8 +575,8 @@ mod solver_tests {
         let field = DefinedField::from_file("generated/patterns/b1.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = super::super::solve(&solver);
 
@@ -572,8 +591,8 @@ mod solver_tests {
         let field = DefinedField::from_file("generated/patterns/h2.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = super::super::solve(&solver);
 
@@ -588,8 +607,8 @@ mod solver_tests {
         let field = DefinedField::from_file("generated/patterns/h3.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = super::super::solve(&solver);
 
@@ -599,3 +618,193 @@ mod solver_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod solution_tests {
+    use super::super::constraint_builder::{Constraint, build_constraints};
+    use super::super::enumerate_solutions;
+    use super::super::sat_solving::{SolutionGraph, solve_component};
+    use super::*;
+    use crate::solver::SearchCounters;
+    use crate::{CancellationToken, GiveUpReason, SolverBudget};
+
+    #[test]
+    fn test_counts_per_mine_count() {
+        // The 1 has three hidden neighbours, exactly one of them is a mine
+        let pattern = "
+            ?M?
+            .1.
+            ...
+        ";
+
+        let field = create_test_field(pattern);
+        let solver = create_solver_with_reveals(&field, &[(1, 1)]);
+
+        let component = vec![(0, 0), (1, 0), (2, 0)];
+        let constraints = build_constraints(&solver, &component);
+        let solutions = enumerate_solutions(&component, &constraints, 3);
+
+        assert_eq!(solutions.solution_counts, vec![0.0, 3.0, 0.0, 0.0]);
+        assert_eq!(solutions.mine_counts[1], vec![1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_respects_mine_limit() {
+        // Both 1s share two hidden cells: one shared mine, or one mine in each corner
+        let pattern = "
+            M?M
+            1?1
+        ";
+
+        let field = create_test_field(pattern);
+        let solver = create_solver_with_reveals(&field, &[(0, 1), (2, 1)]);
+
+        let component = vec![(0, 0), (1, 0), (2, 0), (1, 1)];
+        let constraints = build_constraints(&solver, &component);
+
+        let unlimited = enumerate_solutions(&component, &constraints, 4);
+        let limited = enumerate_solutions(&component, &constraints, 1);
+
+        assert_eq!(unlimited.solution_counts, vec![0.0, 2.0, 1.0, 0.0, 0.0]);
+        assert_eq!(limited.solution_counts, vec![0.0, 2.0]);
+    }
+
+    #[test]
+    fn test_matches_exhaustive_search() {
+        // Pseudo-random overlapping constraints over 12 cells, checked against all 2^12 layouts
+        let component: Vec<(u32, u32)> = (0..12).map(|i| (i, 0)).collect();
+        let mut seed: u32 = 7;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) as usize
+        };
+
+        for _ in 0..20 {
+            let layout: Vec<bool> = (0..component.len()).map(|_| next() % 3 == 0).collect();
+            let constraints: Vec<Constraint> = (0..8)
+                .map(|_| {
+                    let start = next() % component.len();
+                    let fields: Vec<(u32, u32)> = (start
+                        ..component.len().min(start + 1 + next() % 5))
+                        .map(|i| component[i])
+                        .collect();
+                    let mine_count = fields.iter().filter(|f| layout[f.0 as usize]).count() as u32;
+                    Constraint { mine_count, fields }
+                })
+                .collect();
+
+            let solutions = enumerate_solutions(&component, &constraints, 12);
+
+            let mut expected_counts = vec![0.0; component.len() + 1];
+            let mut expected_mines = vec![vec![0.0; component.len()]; component.len() + 1];
+            for bits in 0u32..1 << component.len() {
+                let is_mine = |i: u32| bits & (1 << i) != 0;
+                let valid = constraints.iter().all(|c| {
+                    c.fields.iter().filter(|f| is_mine(f.0)).count() as u32 == c.mine_count
+                });
+                if valid {
+                    let k = bits.count_ones() as usize;
+                    expected_counts[k] += 1.0;
+                    for (i, count) in expected_mines[k].iter_mut().enumerate() {
+                        if is_mine(i as u32) {
+                            *count += 1.0;
+                        }
+                    }
+                }
+            }
+
+            assert_eq!(solutions.solution_counts, expected_counts);
+            assert_eq!(solutions.mine_counts, expected_mines);
+            for (k, safe_counts) in solutions.safe_counts.iter().enumerate() {
+                for (i, &safe_count) in safe_counts.iter().enumerate() {
+                    assert_eq!(safe_count, expected_counts[k] - expected_mines[k][i]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_long_chain_is_counted_without_enumeration() {
+        // 200 cells where every neighbouring pair holds exactly one mine:
+        // two solutions, but the cache keeps the search linear
+        let component: Vec<(u32, u32)> = (0..200).map(|i| (i, 0)).collect();
+        let constraints: Vec<Constraint> = component
+            .windows(2)
+            .map(|pair| Constraint {
+                mine_count: 1,
+                fields: pair.to_vec(),
+            })
+            .collect();
+
+        let graph = SolutionGraph::build(&component, &constraints);
+        assert!(graph.node_count() <= 2 * component.len() + 1);
+
+        let solutions = enumerate_solutions(&component, &constraints, 200);
+        assert_eq!(solutions.solution_counts[100], 2.0);
+        assert_eq!(solutions.solution_counts.iter().sum::<f64>(), 2.0);
+        assert_eq!(solutions.mine_counts[100][0], 1.0);
+        assert_eq!(solutions.safe_counts[100][0], 1.0);
+    }
+
+    #[test]
+    fn test_unsatisfiable_component() {
+        let component = vec![(0, 0), (1, 0)];
+        let constraints = vec![
+            Constraint {
+                mine_count: 2,
+                fields: component.clone(),
+            },
+            Constraint {
+                mine_count: 0,
+                fields: vec![(0, 0)],
+            },
+        ];
+
+        let solutions = enumerate_solutions(&component, &constraints, 2);
+
+        assert!(solutions.solution_counts.iter().all(|&c| c == 0.0));
+        assert!(
+            solve_component(
+                &component,
+                &constraints,
+                2,
+                &SolverBudget::default(),
+                &SearchCounters::default()
+            )
+            .unwrap()
+            .get_mine_fields()
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_search_gives_up() {
+        // 200 separate pairs holding one mine each, every pair is a decision of the search
+        let component: Vec<(u32, u32)> = (0..400).map(|i| (i, 0)).collect();
+        let constraints: Vec<Constraint> = component
+            .chunks(2)
+            .map(|pair| Constraint {
+                mine_count: 1,
+                fields: pair.to_vec(),
+            })
+            .collect();
+
+        let counters = SearchCounters::default();
+        let limited = SolverBudget::new().with_max_search_nodes(50);
+        assert_eq!(
+            SolutionGraph::build_within(&component, &constraints, &limited, &counters).err(),
+            Some(GiveUpReason::NodeLimit)
+        );
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let cancelled = SolverBudget::new().with_cancellation(token);
+        assert_eq!(
+            solve_component(&component, &constraints, 400, &cancelled, &counters).err(),
+            Some(GiveUpReason::Cancelled)
+        );
+
+        let enough = SolverBudget::new().with_max_search_nodes(2000);
+        assert!(SolutionGraph::build_within(&component, &constraints, &enough, &counters).is_ok());
+    }
+}
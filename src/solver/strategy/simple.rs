@@ -1,4 +1,4 @@
-use super::{Finding, Solver};
+use super::{Explanation, Finding, Rule, Solver};
 
 /*
 Simple strategy:
@@ -11,19 +11,30 @@ For each revealed cell with neighbouring hidden cells:
 pub fn solve(solver: &Solver) -> Finding {
     let mut finding = Finding::new();
 
-    for (x, y) in solver.sorted_fields() {
-        if !solver.has_informations(x, y) {
-            continue;
-        }
-
+    // A number's result only depends on its direct neighbours
+    for (x, y) in solver.dirty_frontier(1) {
         let needed_mines = solver.get_reduced_count(x, y);
         let fields = solver.get_surrounding_unrevealed(x, y);
 
+        let constraints = vec![solver.constraint_cell(x, y)];
+
         if needed_mines == 0 {
             // Only add new fields to avoid duplicates
-            finding.add_safe_fields(fields);
+            finding.add_safe_fields(fields.clone());
+            finding.add_explanation(Explanation::new(
+                Rule::SingleCell,
+                constraints,
+                vec![],
+                fields,
+            ));
         } else if needed_mines == fields.len() as u8 {
-            finding.add_mine_fields(fields);
+            finding.add_mine_fields(fields.clone());
+            finding.add_explanation(Explanation::new(
+                Rule::SingleCell,
+                constraints,
+                fields,
+                vec![],
+            ));
         }
     }
 
@@ -33,15 +44,15 @@ pub fn solve(solver: &Solver) -> Finding {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::DefinedField;
+    use crate::{DefinedField, create_solver};
 
     #[test]
     fn test_1_1_pattern() {
         let field = DefinedField::from_file("generated/patterns/1-1.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -57,8 +68,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/1-2.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -74,8 +85,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/1-2-1-R.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -91,8 +102,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/1-2-2-1.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -107,8 +118,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/1-3-1-1.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -123,8 +134,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/1-3-1-2.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -139,8 +150,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/1-3-1-3.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -155,8 +166,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/2-2-2.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -171,8 +182,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/b1.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -187,8 +198,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/h2.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -203,8 +214,8 @@ mod tests {
         let field = DefinedField::from_file("generated/patterns/h3.minesweeper")
             .expect("Failed to load pattern file");
 
-        let mut solver = Solver::new(&field);
-        solver.open_start_cell();
+        let mut solver = create_solver(&field);
+        solver.open_start_cell().unwrap();
 
         let finding = solve(&solver);
 
@@ -0,0 +1,125 @@
+use super::{Finding, SolverView, SolvingStrategy};
+
+/// A deduction rule the solver can run.
+/// Every safe field and mine a strategy reports has to be logically certain,
+/// the solver applies findings without checking them.
+pub trait Strategy: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn solve(&self, view: &SolverView) -> Finding;
+
+    /// How hard the technique is for a player, used by [`crate::difficulty`].
+    /// Ranges from 1 for Simple and MineCount up to 12 for GlobalCount.
+    fn difficulty_weight(&self) -> f64 {
+        5.0
+    }
+}
+
+impl Strategy for SolvingStrategy {
+    fn name(&self) -> &str {
+        self.get_name()
+    }
+
+    fn difficulty_weight(&self) -> f64 {
+        match self {
+            SolvingStrategy::Simple | SolvingStrategy::MineCount => 1.0,
+            SolvingStrategy::Reduction => 3.0,
+            SolvingStrategy::Patterns => 4.0,
+            SolvingStrategy::BoxLogic => 5.0,
+            SolvingStrategy::LinearAlgebra => 6.0,
+            SolvingStrategy::Sat => 8.0,
+            SolvingStrategy::GlobalCount => 12.0,
+        }
+    }
+
+    fn solve(&self, view: &SolverView) -> Finding {
+        self.execute(view.solver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, Solver, SolverConfig, VisibleCell};
+
+    /// The simple strategy rebuilt on top of the public view
+    struct SingleCell;
+
+    impl Strategy for SingleCell {
+        fn name(&self) -> &str {
+            "SingleCell"
+        }
+
+        fn solve(&self, view: &SolverView) -> Finding {
+            let mut finding = Finding::new();
+
+            for (x, y) in view.sorted_fields() {
+                let Some(needed_mines) = view.get_reduced_count(x, y) else {
+                    continue;
+                };
+                let hidden = view.get_surrounding_unrevealed(x, y);
+
+                if needed_mines == 0 {
+                    finding.add_safe_fields(hidden);
+                } else if needed_mines as usize == hidden.len() {
+                    finding.add_mine_fields(hidden);
+                }
+            }
+
+            finding
+        }
+    }
+
+    #[test]
+    fn test_view_of_non_numbers() {
+        let cells = vec![
+            vec![VisibleCell::Revealed(0); 2],
+            vec![VisibleCell::Revealed(1); 2],
+            vec![VisibleCell::Flagged, VisibleCell::Hidden],
+        ];
+        let solver = Solver::from_visible(&cells, 1, &SolverConfig::default()).unwrap();
+        let view = solver.get_view();
+
+        // Only numbers have a reduced count, asking anything else doesn't panic
+        assert_eq!(view.get_reduced_count(1, 0), Some(0));
+        assert_eq!(view.get_reduced_count(0, 0), None);
+        assert_eq!(view.get_reduced_count(2, 0), None);
+        assert_eq!(view.get_reduced_count(2, 1), None);
+        assert_eq!(view.surrounding_fields(0, 0, None).count(), 3);
+        assert_eq!(view.sorted_fields().count(), 6);
+    }
+
+    #[test]
+    fn test_custom_strategy_matches_builtin() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        let builtin = SolverConfig::new(vec![SolvingStrategy::Simple]);
+        let custom = SolverConfig::new(vec![]).with_strategy(SingleCell);
+
+        let mut builtin_solver = Solver::new(&field, &builtin);
+        builtin_solver.solve().unwrap();
+        let mut custom_solver = Solver::new(&field, &custom);
+        custom_solver.solve().unwrap();
+
+        assert!(custom_solver.revealed_count() > 1);
+        assert_eq!(
+            custom_solver.revealed_count(),
+            builtin_solver.revealed_count()
+        );
+    }
+
+    #[test]
+    fn test_custom_strategy_extends_pipeline() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        let config = SolverConfig::new(vec![])
+            .with_strategy(SingleCell)
+            .with_strategy(SolvingStrategy::Sat);
+        let mut solver = Solver::new(&field, &config);
+        solver.solve().unwrap();
+
+        assert!(solver.is_solved());
+    }
+}
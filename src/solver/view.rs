@@ -0,0 +1,97 @@
+use super::{CellState, Solver};
+use crate::Cell;
+
+/// Read-only view of the visible board state of a [`Solver`].
+/// Hidden cells never expose their content, so strategies can only use
+/// the same information a player has.
+pub struct SolverView<'a> {
+    pub(super) solver: &'a Solver,
+}
+
+impl<'a> SolverView<'a> {
+    pub(super) fn new(solver: &'a Solver) -> Self {
+        SolverView { solver }
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.solver.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.solver.height
+    }
+
+    pub fn get_mines(&self) -> u32 {
+        self.solver.mines
+    }
+
+    /// Total mines minus the number of placed flags.
+    pub fn get_remaining_mines(&self) -> u32 {
+        self.solver.get_remaining_mines()
+    }
+
+    pub fn is_hidden(&self, x: u32, y: u32) -> bool {
+        matches!(self.solver.get_state(x, y), CellState::Hidden(_))
+    }
+
+    pub fn is_flagged(&self, x: u32, y: u32) -> bool {
+        matches!(self.solver.get_state(x, y), CellState::Flagged(_))
+    }
+
+    pub fn is_revealed(&self, x: u32, y: u32) -> bool {
+        matches!(self.solver.get_state(x, y), CellState::Revealed(_))
+    }
+
+    /// The number shown on a revealed cell (0 for empty cells), `None` for hidden or flagged cells.
+    pub fn get_number(&self, x: u32, y: u32) -> Option<u8> {
+        match self.solver.get_state(x, y) {
+            CellState::Revealed(Cell::Number(n)) => Some(*n),
+            CellState::Revealed(_) => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Number of a revealed number cell minus its bordering flags,
+    /// `None` for hidden, flagged and empty cells.
+    pub fn get_reduced_count(&self, x: u32, y: u32) -> Option<u8> {
+        self.solver
+            .has_informations(x, y)
+            .then(|| self.solver.get_reduced_count(x, y))
+    }
+
+    pub fn get_surrounding_unrevealed(&self, x: u32, y: u32) -> Vec<(u32, u32)> {
+        self.solver.get_surrounding_unrevealed(x, y)
+    }
+
+    /// True for revealed number cells which still border hidden cells.
+    pub fn has_informations(&self, x: u32, y: u32) -> bool {
+        self.solver.has_informations(x, y)
+    }
+
+    /// Revealed numbers which still border hidden cells, in the order of [`Self::sorted_fields`].
+    pub fn frontier(&self) -> Vec<(u32, u32)> {
+        self.solver.frontier().collect()
+    }
+
+    /// Frontier numbers within `range` of a cell that changed since this strategy last ran.
+    /// On the first run, or when called outside of a solving step, this is the whole frontier.
+    /// Strategies whose result for a number only depends on its surroundings can limit themselves to these.
+    pub fn dirty_frontier(&self, range: u8) -> Vec<(u32, u32)> {
+        self.solver.dirty_frontier(range)
+    }
+
+    /// All cells of the board, row by row.
+    pub fn sorted_fields(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.solver.sorted_fields()
+    }
+
+    /// The cells within `range` of a cell (1 if `None`), without the cell itself.
+    pub fn surrounding_fields(
+        &self,
+        x: u32,
+        y: u32,
+        range: Option<u8>,
+    ) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.solver.surrounding_fields(x, y, range)
+    }
+}
@@ -0,0 +1,274 @@
+use super::view::SolverView;
+use super::{CellState, Finding, Solver, SolverConfig, SolverError};
+use crate::Cell;
+
+/*
+Visible boards:
+
+A solver built with Solver::from_visible only knows what a player sees: revealed numbers, flags
+and the total mine count. Hidden cells carry no content, so nothing can be revealed and flags
+can't be checked against the field. Such a solver is used as a hint engine, hint() runs the
+strategies on the board as it is and returns their deductions without applying them.
+Flags are trusted, a wrong flag leads to wrong hints.
+*/
+
+/// What a player sees of a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisibleCell {
+    Hidden,
+    Flagged,
+    /// A revealed cell with the number it shows, 0 for empty cells
+    Revealed(u8),
+}
+
+impl Solver {
+    /// Builds a solver from the visible state of a board, indexed `[x][y]`, and its total mine count.
+    /// The solver never learns the content of hidden cells, use [`Solver::hint`] to get deductions.
+    /// [`Solver::solve`] stops with [`SolverError::UnknownCell`] as soon as it would have to reveal a cell.
+    pub fn from_visible(
+        cells: &[Vec<VisibleCell>],
+        mines: u32,
+        config: &SolverConfig,
+    ) -> Result<Solver, SolverError> {
+        let width = cells.len() as u32;
+        let height = cells.first().map_or(0, |column| column.len()) as u32;
+
+        if width == 0 || height == 0 {
+            return Err(SolverError::InvalidBoard("the board is empty".into()));
+        }
+        if cells.iter().any(|column| column.len() != height as usize) {
+            return Err(SolverError::InvalidBoard(
+                "all columns need the same height".into(),
+            ));
+        }
+
+        // Any revealed cell counts as the opened start
+        let start_cell = (0..width)
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .find(|&(x, y)| matches!(cells[x as usize][y as usize], VisibleCell::Revealed(_)))
+            .unwrap_or((0, 0));
+
+        let mut solver = Solver::with_state(
+            vec![vec![CellState::Hidden(None); height as usize]; width as usize],
+            mines,
+            start_cell,
+            config,
+        );
+
+        for (x, y) in solver.sorted_fields() {
+            let state = match cells[x as usize][y as usize] {
+                VisibleCell::Hidden => continue,
+                VisibleCell::Flagged => CellState::Flagged(Cell::Mine),
+                VisibleCell::Revealed(0) => CellState::Revealed(Cell::Empty),
+                VisibleCell::Revealed(n) if n <= 8 => CellState::Revealed(Cell::Number(n)),
+                VisibleCell::Revealed(n) => {
+                    return Err(SolverError::InvalidBoard(format!(
+                        "({}, {}) shows {}, a cell has at most 8 neighbours",
+                        x, y, n
+                    )));
+                }
+            };
+            solver.set_state(x, y, state);
+        }
+
+        if solver.incremental.flagged > mines {
+            return Err(SolverError::InvalidBoard(format!(
+                "{} flags placed but only {} mines",
+                solver.incremental.flagged, mines
+            )));
+        }
+        solver.check_visible_numbers()?;
+
+        Ok(solver)
+    }
+
+    /// Every revealed number needs room for its mines and may not border more flags than it shows
+    fn check_visible_numbers(&self) -> Result<(), SolverError> {
+        for (x, y) in self.sorted_fields() {
+            let number = match self.get_state(x, y) {
+                CellState::Revealed(Cell::Number(n)) => *n,
+                CellState::Revealed(_) => 0,
+                _ => continue,
+            };
+
+            let flags = self.get_surrounding_flag_count(x, y);
+            if flags > number {
+                return Err(SolverError::InconsistentFlags {
+                    x,
+                    y,
+                    number,
+                    flags,
+                });
+            }
+            if flags + self.get_surrounding_unrevealed_count(x, y) < number {
+                return Err(SolverError::InvalidBoard(format!(
+                    "the {} at ({}, {}) has not enough hidden neighbours for its mines",
+                    number, x, y
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// What a player sees of the current board, indexed `[x][y]`.
+    /// Can be fed back into [`Solver::from_visible`].
+    pub fn get_visible_board(&self) -> Vec<Vec<VisibleCell>> {
+        self.state
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|state| match state {
+                        CellState::Hidden(_) => VisibleCell::Hidden,
+                        CellState::Flagged(_) => VisibleCell::Flagged,
+                        CellState::Revealed(Cell::Number(n)) => VisibleCell::Revealed(*n),
+                        CellState::Revealed(_) => VisibleCell::Revealed(0),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Runs the configured strategies on the current board without changing it.
+    /// Returns the finding of the first strategy which makes progress, `None` if a guess is needed.
+    pub fn hint(&self) -> Option<Finding> {
+        let view = SolverView::new(self);
+
+        self.config
+            .get_strategies()
+            .iter()
+            .map(|strategy| strategy.solve(&view))
+            .find(|finding| finding.success())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefinedField, MineSweeperField, SolveOutcome, create_solver};
+
+    #[test]
+    fn test_hints_follow_the_game() {
+        let field = DefinedField::from_file("generated/testing/hard.minesweeper")
+            .expect("Failed to load testing file");
+
+        // Play the game with hints only, the field is consulted just like a player clicking
+        let mut game = create_solver(&field);
+        game.open_start_cell().unwrap();
+
+        let mut hints = 0;
+        loop {
+            let visible = Solver::from_visible(
+                &game.get_visible_board(),
+                field.get_mines(),
+                &SolverConfig::default(),
+            )
+            .unwrap();
+
+            let Some(hint) = visible.hint() else {
+                break;
+            };
+            hints += 1;
+
+            let mut revealed = Vec::new();
+            for &(x, y) in hint.get_safe_fields() {
+                assert_ne!(field.get_cell(x, y), &Cell::Mine);
+                game.reveal_cell(x, y, &mut revealed).unwrap();
+            }
+            for &(x, y) in hint.get_mine_fields() {
+                assert_eq!(field.get_cell(x, y), &Cell::Mine);
+                game.flag_cell(x, y).unwrap();
+            }
+        }
+
+        assert!(hints > 0);
+        assert!(game.is_solved());
+    }
+
+    #[test]
+    fn test_hint_does_not_change_the_board() {
+        // The last column hides one mine in a 50/50
+        let h = VisibleCell::Hidden;
+        let cells = vec![
+            vec![VisibleCell::Revealed(0); 2],
+            vec![VisibleCell::Revealed(0); 2],
+            vec![VisibleCell::Revealed(1), VisibleCell::Revealed(1)],
+            vec![h, h],
+        ];
+
+        let solver = Solver::from_visible(&cells, 1, &SolverConfig::default()).unwrap();
+        assert!(solver.hint().is_none());
+
+        let cells = vec![
+            vec![VisibleCell::Revealed(0); 2],
+            vec![VisibleCell::Revealed(1), VisibleCell::Revealed(1)],
+            vec![h, VisibleCell::Revealed(1)],
+        ];
+        let solver = Solver::from_visible(&cells, 1, &SolverConfig::default()).unwrap();
+        let hint = solver.hint().expect("The corner is a mine");
+
+        assert_eq!(hint.get_mine_fields(), &vec![(2, 0)]);
+        assert!(matches!(solver.get_state(2, 0), CellState::Hidden(None)));
+        assert_eq!(solver.revealed_count(), 5);
+    }
+
+    #[test]
+    fn test_solve_cannot_reveal_unknown_cells() {
+        let h = VisibleCell::Hidden;
+        let cells = vec![
+            vec![VisibleCell::Revealed(0); 2],
+            vec![VisibleCell::Revealed(1), VisibleCell::Revealed(1)],
+            vec![h, h],
+        ];
+        let mut solver = Solver::from_visible(&cells, 1, &SolverConfig::default()).unwrap();
+
+        // The two hidden cells can't be told apart, but revealing would need the field
+        assert_eq!(solver.solve(), Ok(SolveOutcome::Stuck));
+
+        let cells = vec![
+            vec![VisibleCell::Revealed(0); 2],
+            vec![VisibleCell::Revealed(1), VisibleCell::Revealed(1)],
+            vec![VisibleCell::Flagged, h],
+        ];
+        let mut solver = Solver::from_visible(&cells, 1, &SolverConfig::default()).unwrap();
+
+        assert_eq!(solver.solve(), Err(SolverError::UnknownCell { x: 2, y: 1 }));
+    }
+
+    #[test]
+    fn test_invalid_boards() {
+        let h = VisibleCell::Hidden;
+        let config = SolverConfig::default();
+
+        assert!(matches!(
+            Solver::from_visible(&[], 1, &config),
+            Err(SolverError::InvalidBoard(_))
+        ));
+        assert!(matches!(
+            Solver::from_visible(&[vec![h, h], vec![h]], 1, &config),
+            Err(SolverError::InvalidBoard(_))
+        ));
+        assert!(matches!(
+            Solver::from_visible(&[vec![VisibleCell::Revealed(2), h]], 1, &config),
+            Err(SolverError::InvalidBoard(_))
+        ));
+        assert_eq!(
+            Solver::from_visible(
+                &[
+                    vec![VisibleCell::Revealed(1), VisibleCell::Flagged],
+                    vec![VisibleCell::Flagged, h]
+                ],
+                2,
+                &config
+            )
+            .err(),
+            Some(SolverError::InconsistentFlags {
+                x: 0,
+                y: 0,
+                number: 1,
+                flags: 2
+            })
+        );
+    }
+}